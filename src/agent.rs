@@ -1,20 +1,39 @@
 //! Implements functions for the `Player` trait from a computer's perspective.
 
-use std::collections::HashMap;
-
 use crate::{
     Card,
     Action,
     Player,
     PerceivedHand,
     ActionUtilities,
+    InfluenceWeights,
+    TargetWeights,
+    BlockWeights,
+    UtilityModel,
     PlayerMetadata,
+    GameEvent,
+    GameView,
+    Belief,
+    Distribution,
 };
 
 use rand::{
-    random,
+    Rng,
+    SeedableRng,
+    rngs::StdRng,
+    seq::SliceRandom,
 };
 
+/// The cost of losing the last card, and with it the game, relative to
+/// losing any other card.
+const LAST_CARD: f64 = 3.0;
+
+/// How much more a margin between a belief and a cutoff counts, at a given
+/// temperature, than the same difference in utility when choosing an
+/// action.  Beliefs and cutoffs are probabilities, which span about a tenth
+/// of the range that utilities do (see `soft`).
+const MARGIN_SCALE: f64 = 10.0;
+
 /// Holds the information and performs the actions of a player.
 #[derive(Clone, Debug)]
 pub struct Agent {
@@ -36,11 +55,32 @@ pub struct Agent {
     /// Stores the utilities for each action.
     utilities: ActionUtilities,
 
+    /// Weighs the reasons for keeping each card when losing an influence.
+    influence: InfluenceWeights,
+
+    /// Weighs the features of an opponent when picking whom to attack.
+    targeting: TargetWeights,
+
+    /// Weighs the reasons for blocking, and for bluffing a block.
+    blocking: BlockWeights,
+
+    /// Adjusts the utilities to the state of the game.
+    model: UtilityModel,
+
+    /// Controls how often this player strays from its best decision.
+    temperature: f64,
+
     /// Stores the number of opponents this player is playing.
     opponents: usize,
 
     /// Holds the perceived hands of the other players.
     perceived_hands: Vec<PerceivedHand>,
+
+    /// Holds what this player believes about the other players' hands.
+    beliefs: Belief,
+
+    /// Drives every random decision this player makes.
+    rng: StdRng,
 }
 
 impl Player for Agent {
@@ -50,6 +90,11 @@ impl Player for Agent {
             lying_cutoff: self.lying_cutoff,
             liar_cutoff: self.liar_cutoff,
             utilities: self.utilities,
+            influence: self.influence,
+            targeting: self.targeting,
+            blocking: self.blocking,
+            model: self.model,
+            temperature: self.temperature,
         }
     }
 
@@ -72,10 +117,47 @@ impl Player for Agent {
 
     /// Exchange cards (used by `Ambassador`).
     /// 
-    /// Right now, this discards the two cards drawn.  This will
-    /// be changed to be more strategic in the future.
-    fn exchange(&mut self, cards: &[Card]) -> Vec<Card> {
-        cards.to_vec()
+    /// This scores every hand this player could keep from its own cards and
+    /// the cards drawn (see `Agent::score_hand`), keeps the best one, and
+    /// returns the rest.  With one influence left, only one card is kept.
+    fn exchange(&mut self, view: &GameView, cards: &[Card]) -> Vec<Card> {
+        // The beliefs feed the scores, so bring them up to date
+        self.beliefs.update(view);
+
+        let mut pool = self.hand.iter()
+            .copied()
+            .filter(|c| *c != Card::None)
+            .collect::<Vec<Card>>();
+        let influence = pool.len();
+        pool.extend_from_slice(cards);
+
+        // List every way to keep as many cards as this player has influence
+        let keeps: Vec<Vec<usize>> = match influence {
+            2 => (0..pool.len()).flat_map(|i| (i + 1..pool.len()).map(move |j| vec![i, j])).collect(),
+            1 => (0..pool.len()).map(|i| vec![i]).collect(),
+            _ => vec![Vec::new()],
+        };
+
+        // Note: it's OK to use `Option::unwrap` here because we know
+        // we are comparing valid `f64`s computed from our utility table.
+        // Ties go to the earliest hand, which favors the cards already held.
+        let scores = keeps.iter()
+            .map(|keep| self.score_hand(view, &keep.iter().map(|&i| pool[i]).collect::<Vec<Card>>()))
+            .collect::<Vec<f64>>();
+        let best = (0..keeps.len())
+            .rev()
+            .max_by(|&a, &b| scores[a].partial_cmp(&scores[b]).unwrap())
+            .unwrap_or(0);
+
+        self.hand = [Card::None, Card::None];
+        for (slot, &i) in keeps[best].iter().enumerate() {
+            self.hand[slot] = pool[i];
+        }
+
+        (0..pool.len())
+            .filter(|i| !keeps[best].contains(i))
+            .map(|i| pool[i])
+            .collect()
     }
 
     /// Gets the number of coins this player has.
@@ -83,90 +165,26 @@ impl Player for Agent {
         self.coins
     }
 
-    /// Computes *a priori* probabilities of each player having certain cards.
-    fn compute_hands(&mut self, killed: &[Card]) {
-        let mut hands = Vec::new();
-
-        // Count cards
-
-        // This count is private (based on private information)
-        let mut available: f64 = 15.0;
-        let mut counts = HashMap::<Card, f64>::from([
-            (Card::Duke, 3.0),
-            (Card::Captain, 3.0),
-            (Card::Ambassador, 3.0),
-            (Card::Assassin, 3.0),
-            (Card::Contessa, 3.0),
-        ]);
-
-        // This count is public (based on public information)
-        let mut public_available: f64 = 15.0;
-        let mut public_counts = HashMap::<Card, f64>::from([
-            (Card::Duke, 3.0),
-            (Card::Captain, 3.0),
-            (Card::Ambassador, 3.0),
-            (Card::Assassin, 3.0),
-            (Card::Contessa, 3.0),
-        ]);
-
-        // Remove any "dead" cards
-        for card in killed {
-            let current: f64 = match counts.get(&card) {
-                Some(v) => *v,
-                None => todo!(),
-            };
-            counts.insert(*card, current - 1.0);
-            public_counts.insert(*card, current - 1.0);
-
-            available -= 1.0;
-            public_available -= 1.0;
-        }
-
-        // Remove your current two cards
-        if let Some(count) = counts.get(&self.hand[0]) {
-            counts.insert(self.hand[0], count - 1.0);
-        }
+    /// Computes the probabilities of each player having certain cards.
+    /// 
+    /// The other players' hands come from this player's beliefs, which are
+    /// updated on everything that has happened since the last turn.  This
+    /// player's own hand is estimated as the others would see it, from the
+    /// public information alone.
+    fn compute_hands(&mut self, view: &GameView) {
+        self.beliefs.update(view);
 
-        if let Some(count) = counts.get(&self.hand[1]) {
-            counts.insert(self.hand[1], count - 1.0);
-        }
+        let cards = view.rules.cards();
+        let public = Distribution::public(view);
 
-        available -= 2.0;
-
-        // Find the *a priori* probabilities for other people's hands
-        // Note: it's OK to use `Option::unwrap` here because we know we inserted each of these
-        // cards into the hashmap `counts` above
-        let a_priori = HashMap::from([
-            (Card::Duke, 1.0 - ((available - counts.get(&Card::Duke).unwrap())/available)*(available - 1.0 - counts.get(&Card::Duke).unwrap())/(available - 1.0)),
-            (Card::Captain, 1.0 - ((available - counts.get(&Card::Captain).unwrap())/available)*(available - 1.0 - counts.get(&Card::Captain).unwrap())/(available - 1.0)),
-            (Card::Ambassador, 1.0 - ((available - counts.get(&Card::Ambassador).unwrap())/available)*(available - 1.0 - counts.get(&Card::Ambassador).unwrap())/(available - 1.0)),
-            (Card::Assassin, 1.0 - ((available - counts.get(&Card::Assassin).unwrap())/available)*(available - 1.0 - counts.get(&Card::Assassin).unwrap())/(available - 1.0)),
-            (Card::Contessa, 1.0 - ((available - counts.get(&Card::Contessa).unwrap())/available)*(available - 1.0 - counts.get(&Card::Contessa).unwrap())/(available - 1.0)),
-        ]);
-
-        // Find the *a priori* probabilities for my hand
-        // Note: it's OK to use `Option::unwrap` here because we know we inserted each of these
-        // cards into the hashmap `counts` above
-        let my_hand = HashMap::from([
-            (Card::Duke, 1.0 - ((public_available - public_counts.get(&Card::Duke).unwrap())/public_available)*(public_available - 1.0 - public_counts.get(&Card::Duke).unwrap())/(public_available - 1.0)),
-            (Card::Captain, 1.0 - ((public_available - public_counts.get(&Card::Captain).unwrap())/public_available)*(public_available - 1.0 - public_counts.get(&Card::Captain).unwrap())/(public_available - 1.0)),
-            (Card::Ambassador, 1.0 - ((public_available - public_counts.get(&Card::Ambassador).unwrap())/public_available)*(public_available - 1.0 - public_counts.get(&Card::Ambassador).unwrap())/(public_available - 1.0)),
-            (Card::Assassin, 1.0 - ((public_available - public_counts.get(&Card::Assassin).unwrap())/public_available)*(public_available - 1.0 - public_counts.get(&Card::Assassin).unwrap())/(public_available - 1.0)),
-            (Card::Contessa, 1.0 - ((public_available - public_counts.get(&Card::Contessa).unwrap())/public_available)*(public_available - 1.0 - public_counts.get(&Card::Contessa).unwrap())/(public_available - 1.0)),
-        ]);
-        
         // Fill the list of perceived hands
-        for i in 0..=self.opponents {
-            let hand = if i == self.id {
-                PerceivedHand::from(my_hand.clone())
+        self.perceived_hands = (0..=self.opponents)
+            .map(|i| if i == self.id {
+                PerceivedHand::from_hands(&cards, &public.hands(i))
             } else {
-                PerceivedHand::from(a_priori.clone())
-            };
-
-            hands.push(hand);
-        }
-
-        self.perceived_hands = hands;
+                PerceivedHand::from_hands(&cards, &self.beliefs.hands(i))
+            })
+            .collect();
     }
 
     /// Deals the given cards to the player.
@@ -174,6 +192,27 @@ impl Player for Agent {
         self.hand = hand;
     }
 
+    /// Chooses the first card of this player's hand.
+    /// 
+    /// Each card is scored the way an exchange scores a hand of one card
+    /// (see `score_hand`), so both choices weigh cards alike.
+    fn choose_influence(&mut self, view: &GameView, cards: &[Card]) -> Card {
+        // The beliefs feed the scores, so bring them up to date
+        self.beliefs.update(view);
+
+        // Note: it's OK to use `Option::unwrap` here because we know
+        // we are comparing valid `f64`s computed from our utility table.
+        // Ties go to the earliest card.
+        let scores = cards.iter()
+            .map(|card| self.score_hand(view, &[*card]))
+            .collect::<Vec<f64>>();
+        (0..cards.len())
+            .rev()
+            .max_by(|&a, &b| scores[a].partial_cmp(&scores[b]).unwrap())
+            .map(|i| cards[i])
+            .unwrap_or(Card::None)
+    }
+
     /// Gains the number of coins specified.
     fn gain_coins(&mut self, coins: u8) {
         self.coins += coins;
@@ -193,19 +232,26 @@ impl Player for Agent {
 
     /// Forces the player to lose one influence.
     /// 
-    /// Right now, this is a random selection.  It will be trained later, probably using
-    /// reinforcement learning or regret minimization.
-    fn lose_influence(&mut self) -> Card {
-        let lost = if self.hand[0] == Card::None && self.hand[1] != Card::None {
-            1
-        } else if self.hand[1] == Card::None && self.hand[0] != Card::None {
-            0
-        } else if random() {
-            1
-        } else {
-            0
+    /// This keeps the card worth the most by this player's influence
+    /// weights: what its action is worth, what the attacks it blocks are
+    /// worth (given who is believed to be able to make them), and, if this
+    /// player has claimed it, what keeping the claim credible is worth.
+    fn lose_influence(&mut self, view: &GameView) -> Card {
+        self.beliefs.update(view);
+        let claimed = self.claimed(view);
+
+        let worth = |card: Card| {
+            let claim = if claimed.contains(&card) { self.influence.claim } else { 0.0 };
+            (self.influence.action + claim)*self.action_value(card) + self.influence.defense*self.defense_value(view, card)
         };
 
+        // Note: it's OK to use `Option::unwrap` here because we know
+        // we are comparing valid `f64`s computed from our utility table
+        let lost = (0..2)
+            .filter(|&i| self.hand[i] != Card::None)
+            .min_by(|&a, &b| worth(self.hand[a]).partial_cmp(&worth(self.hand[b])).unwrap())
+            .unwrap_or(0);
+
         let card = self.hand[lost];
         self.hand[lost] = Card::None;
 
@@ -216,9 +262,10 @@ impl Player for Agent {
     /// 
     /// Returns `true` if the player challenges and `false` otherwise.
     /// 
-    /// Right now, this is based on a trained "liar" threshold.  This may
-    /// change in the future.
-    fn check_challenge(&self, active_player: usize, card: Card) -> bool {
+    /// This challenges when the chance that the claimant is lying is high
+    /// enough to be worth the risk (see `challenge_threshold`), softened by
+    /// the temperature (see `soft`).
+    fn check_challenge(&mut self, view: &GameView, claimant: usize, card: Card) -> bool {
         if card == Card::None {
             // You can't challenge Income, ForeignAid, or Coup
             return false;
@@ -229,10 +276,22 @@ impl Player for Agent {
             return false;
         }
 
-        // Note: it's OK to use `Option::unwrap` here because we know we're providing
-        // one of the five game cards (we just checked `Card::None`) and we know
-        // for sure that each of these cards are in each of our perceived hands
-        self.perceived_hands[active_player].get(&card).unwrap() < &self.liar_cutoff
+        // The claim itself is part of the history, so bring the beliefs up
+        // to date before judging it
+        self.beliefs.update(view);
+        let probability = self.beliefs.probability(claimant, card);
+
+        let lie = if view.current == Some (Action::Embezzle) {
+            // The claimant is lying if they do have the card
+            probability
+        } else {
+            1.0 - probability
+        };
+
+        match self.challenge_threshold(view, claimant, lie) {
+            Some (threshold) => self.soft(lie - threshold),
+            None => false,
+        }
     }
 
     /// Asks this player if he blocks an action.
@@ -240,64 +299,129 @@ impl Player for Agent {
     /// Returns `true` if the player blocks and `false` otherwise.  Also returns
     /// the card with which the player blocks.
     /// 
-    /// Right now, this is based on a trained "lying" threshold.  This may
-    /// change in the future.
-    /// 
-    /// Right now, this player is "perfectly selfish": it will only block actions
-    /// against it.  This may change in the future.
-    fn check_block(&self, action: Action) -> (bool, Card) {
+    /// This player blocks attacks on itself, and Foreign Aid on behalf of the
+    /// whole table.  Of the cards that block an action, it picks the most
+    /// credible (see `block_credibility`).  It blocks honestly whenever it
+    /// can.  Otherwise, it bluffs when the chance of being challenged is low
+    /// enough for the block to be worth the risk, softened by the temperature
+    /// (see `soft`); a bluffed block of Foreign Aid is worth more the closer
+    /// the aid would bring the actor to a Coup, scaled by the aid weight.
+    fn check_block(&mut self, view: &GameView, action: Action) -> (bool, Card) {
         if self.is_eliminated() {
             // You can't block if you're out
             return (false, Card::None);
         }
 
-        // Note: it's OK to use `Option::unwrap` here because we know we're providing one
-        // of the five game cards (we are sure we haven't passed `Card::None`) and we know
-        // for sure that each of these cards are in each of our perceived hands
-        match action {
-            Action::ForeignAid => (self.perceived_hands[self.id].get(&Card::Duke).unwrap() > &self.lying_cutoff, Card::Duke),
-            Action::Assassinate (i) => (i == self.id && self.perceived_hands[self.id].get(&Card::Contessa).unwrap() > &self.lying_cutoff, Card::Duke),
-            Action::Steal (i) => {
-                // For now, only block actions against yourself
-                if i != self.id {
-                    return (false, Card::None);
-                }
-
-                let captain = self.perceived_hands[self.id].get(&Card::Captain).unwrap();
-                let ambassador = self.perceived_hands[self.id].get(&Card::Ambassador).unwrap();
+        if view.active_player == self.id {
+            // You can't block your own action
+            return (false, Card::None);
+        }
 
-                if captain > ambassador {
-                    return (captain > &self.lying_cutoff, Card::Captain);
-                } else {
-                    return (ambassador > &self.lying_cutoff, Card::Ambassador);
-                }
+        // What the block saves this player, and what being caught bluffing
+        // costs it on top of that, in cards (see `challenge_threshold`)
+        let rules = &view.rules;
+        let coins = |coins: u8| coins as f64/rules.coup_cost as f64;
+        let lost = self.lost_cost(view, 1);
+        let (worth, caught) = match action {
+            Action::ForeignAid => {
+                let actor = view.players[view.active_player].coins.saturating_add(rules.foreign_aid);
+                (self.blocking.aid*coins(actor).min(1.0), lost)
             },
-            _ => (false, Card::None),
+            Action::Assassinate (i) if i == self.id => (lost, self.lost_cost(view, 2) - lost),
+            Action::Steal (i) if i == self.id => (coins(rules.steal.min(view.players[self.id].coins)), lost),
+            _ => return (false, Card::None),
+        };
+
+        // Note: it's OK to use `Option::unwrap` here because every action
+        // this player blocks has at least one blocking card
+        let claimed = self.claimed(view);
+        let (card, credibility) = rules.blocking_cards(action)
+            .into_iter()
+            .map(|card| (card, self.block_credibility(card, &claimed)))
+            .reduce(|best, other| if other.1 > best.1 { other } else { best })
+            .unwrap();
+
+        if self.hand.contains(&card) {
+            return (true, card);
+        }
+
+        if worth <= 0.0 {
+            return (false, Card::None);
+        }
+
+        // Bluffing is worth it if the chance of being challenged is below the
+        // share of the stakes that the block saves
+        let challenged = (self.blocking.suspicion*(1.0 - credibility)).clamp(0.0, 1.0);
+        (self.soft(worth/(worth + caught) - challenged), card)
+    }
+
+    /// Shows a card to a player examining this one.
+    /// 
+    /// Right now, this shows either of its cards at random, so the examiner
+    /// learns nothing from which one it sees.
+    fn show_card(&mut self, _: &GameView, _: usize) -> Card {
+        let live = self.hand.iter()
+            .copied()
+            .filter(|c| *c != Card::None)
+            .collect::<Vec<Card>>();
+
+        match live[..] {
+            [a, b] => if self.rng.gen() { a } else { b },
+            [a] => a,
+            _ => Card::None,
         }
     }
 
+    /// Decides whether or not to force an examined player to swap a card.
+    /// 
+    /// Right now, this player swaps away any card that can be used to take
+    /// coins or influence from other players.
+    fn check_examine(&mut self, _: &GameView, _: usize, card: Card) -> bool {
+        matches!(card, Card::Duke | Card::Assassin | Card::Captain)
+    }
+
     /// Checks whether or not a player is still in the game.
     fn is_eliminated(&self) -> bool {
         self.hand == [Card::None, Card::None]
     }
 
     /// Select an action based on actions available.
-    fn select_action(&self, eliminated_players: &[usize]) -> Action {
-        let actions = self.get_available_actions(eliminated_players);
+    /// 
+    /// At temperature 0, this is always the most useful action.  Otherwise,
+    /// each action is picked with a probability proportional to
+    /// `exp(utility/temperature)`, so that better actions are played more
+    /// often but none are entirely predictable.
+    fn select_action(&mut self, view: &GameView) -> Action {
+        self.beliefs.update(view);
+        let actions = self.get_available_actions(view);
 
         // Compute the utility of each action
         let mut utilities = actions.iter()
-            .map(|a| (*a, self.compute_utility(*a)))
+            .map(|a| (*a, self.compute_utility(view, *a)))
             .collect::<Vec<(Action, f64)>>();
         
         // Note: it's OK to use `Result::unwrap` here because we know
         // we are passing a valid `f64` from our utility table
         utilities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
 
-        match utilities.iter().nth(0) {
-            Some(action) => action.0,
-            None => Action::Pass,
+        let best = match utilities.first() {
+            Some(action) => *action,
+            None => return Action::Pass,
+        };
+
+        if self.temperature <= 0.0 {
+            return best.0;
         }
+
+        // Measure utilities from the best, so the weights cannot overflow
+        let temperature = self.temperature;
+        utilities.choose_weighted(&mut self.rng, |a| ((a.1 - best.1)/temperature).exp())
+            .map_or(best.0, |a| a.0)
+    }
+
+    /// Reseeds this player's random number generator.
+    fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
     }
 }
 
@@ -305,15 +429,36 @@ impl Player for Agent {
 impl Agent {
     /// Generates a new (random) player.
     pub fn new(id: usize, opponents: usize) -> Self {
+        Self::from_rng(id, opponents, StdRng::from_entropy())
+    }
+
+    /// Generates a new (random) player whose parameters and decisions are
+    /// derived from the given seed.
+    pub fn with_seed(id: usize, opponents: usize, seed: u64) -> Self {
+        Self::from_rng(id, opponents, StdRng::seed_from_u64(seed))
+    }
+
+    /// Generates a new (random) player from the given random number generator.
+    fn from_rng(id: usize, opponents: usize, mut rng: StdRng) -> Self {
         Self {
             id,
             hand: [Card::None, Card::None],
-            coins: 2,
-            liar_cutoff: random(),
-            lying_cutoff: random(),
-            utilities: ActionUtilities::random(),
+            coins: 0,
+            liar_cutoff: rng.gen(),
+            lying_cutoff: rng.gen(),
+            utilities: ActionUtilities::random(&mut rng),
+            influence: InfluenceWeights::random(&mut rng),
+            targeting: TargetWeights::random(&mut rng),
+            blocking: BlockWeights::random(&mut rng),
+            model: UtilityModel::random(&mut rng),
+
+            // Random agents play deterministically until mutation moves
+            // their temperature off zero
+            temperature: 0.0,
             opponents,
             perceived_hands: Vec::new(),
+            beliefs: Belief::new(),
+            rng,
         }
     }
 
@@ -323,16 +468,28 @@ impl Agent {
             liar_cutoff,
             lying_cutoff,
             utilities,
+            influence,
+            targeting,
+            blocking,
+            model,
+            temperature,
         } = metadata {
             Self {
                 id,
                 hand: [Card::None, Card::None],
-                coins: 2,
+                coins: 0,
                 liar_cutoff,
                 lying_cutoff,
                 utilities,
+                influence,
+                targeting,
+                blocking,
+                model,
+                temperature,
                 opponents,
                 perceived_hands: Vec::new(),
+                beliefs: Belief::new(),
+                rng: StdRng::from_entropy(),
             }
         } else {
             unreachable!();
@@ -353,139 +510,385 @@ impl Agent {
             liar_cutoff: self.liar_cutoff,
             lying_cutoff: self.lying_cutoff,
             utilities: self.utilities,
+            influence: self.influence,
+            targeting: self.targeting,
+            blocking: self.blocking,
+            model: self.model,
+            temperature: self.temperature,
             opponents: self.opponents,
             perceived_hands: self.perceived_hands,
+            beliefs: self.beliefs,
+            rng: self.rng,
         }
     }
 
-    /// "Mutates" this player by slightly modifying the cutoff probabilities.
-    pub fn mutate(&self) -> Self {
+    /// "Mutates" this player by slightly modifying the cutoff probabilities,
+    /// the utilities, the influence, target, and block weights, the utility
+    /// model, and the temperature.
+    /// 
+    /// The mutation is drawn from this player's RNG, and the new player is
+    /// given its own RNG seeded from this one.
+    pub fn mutate(&mut self) -> Self {
         Self {
             id: self.id,
             hand: [Card::None, Card::None],
-            coins: 2,
-            liar_cutoff: self.liar_cutoff + 0.01 * (2.0*random::<f64>() - 1.0),
-            lying_cutoff: self.lying_cutoff + 0.01 * (2.0*random::<f64>() - 1.0),
-            utilities: self.utilities.mutate(),
+            coins: 0,
+            liar_cutoff: self.liar_cutoff + 0.01 * (2.0*self.rng.gen::<f64>() - 1.0),
+            lying_cutoff: self.lying_cutoff + 0.01 * (2.0*self.rng.gen::<f64>() - 1.0),
+            utilities: self.utilities.mutate(&mut self.rng),
+            influence: self.influence.mutate(&mut self.rng),
+            targeting: self.targeting.mutate(&mut self.rng),
+            blocking: self.blocking.mutate(&mut self.rng),
+            model: self.model.mutate(&mut self.rng),
+            temperature: (self.temperature + 0.01 * (2.0*self.rng.gen::<f64>() - 1.0)).max(0.0),
             opponents: self.opponents,
             perceived_hands: Vec::new(),
+            beliefs: Belief::new(),
+            rng: StdRng::seed_from_u64(self.rng.gen()),
         }
     }
 
     /// Prepares this player for the next game.
     pub fn clear(&mut self) {
         self.hand = [Card::None, Card::None];
-        self.coins = 2;
+        self.coins = 0;
         self.perceived_hands = Vec::new();
+        self.beliefs = Belief::new();
     }
 
-    /// Selects the list of actions available to the player.
-    fn get_available_actions(&self, eliminated_players: &[usize]) -> Vec<Action> {
-        // If this player is eliminated, he must Pass.
-        if self.is_eliminated() {
-            return vec![Action::Pass];
-        }
+    /// Selects the list of actions this player is willing to take.
+    /// 
+    /// The legal actions come from the engine; this player only takes actions
+    /// that require a card it has, or that it is willing to lie about.
+    fn get_available_actions(&self, view: &GameView) -> Vec<Action> {
+        view.legal_actions.iter()
+            .copied()
+            .filter(|&action| {
+                // Embezzling means claiming not to have a Duke, which is
+                // plausible unless this player is believed to have one
+                // Note: it's OK to use `Option::unwrap` here because we know we put `Card::Duke`
+                // into the PerceivedHand hashmap earlier in the program
+                if action == Action::Embezzle {
+                    return !self.hand.contains(&Card::Duke) || self.perceived_hands[self.id].get(&Card::Duke).unwrap() < &(1.0 - self.lying_cutoff);
+                }
 
-        let mut actions: Vec<Action> = Vec::new();
+                let card = match action {
+                    Action::Tax => Card::Duke,
+                    Action::Assassinate (_) => Card::Assassin,
+                    Action::Exchange => view.rules.exchange_card(),
+                    Action::Examine (_) => Card::Inquisitor,
+                    Action::Steal (_) => Card::Captain,
+                    _ => return true,
+                };
+
+                // For each card we have, we can take "safe" actions
+
+                // Now we get to the lying: "dangerous" actions
+                // Which actions can I take that are deceptive but will not injure me seriously?
+
+                // Note: it's OK to use `Option::unwrap` here because we know we put each of these cards into the PerceivedHand hashmap
+                // earlier in the program
+                self.hand.contains(&card) || self.perceived_hands[self.id].get(&card).unwrap() > &self.lying_cutoff
+            })
+            .collect()
+    }
 
-        // If this player has 10 coins or more, he must Coup.
-        if self.coins >= 10 {
-            for i in 0..=self.opponents {
-                if i != self.id && !eliminated_players.contains(&i) {
-                    actions.push(Action::Coup (i));
+    /// Scores a hand this player could keep after an exchange.
+    /// 
+    /// Each card is worth the utility of the action it lets this player take
+    /// honestly, plus the utility of each attack on this player it blocks,
+    /// weighted by the chance that some opponent holds the attacking card.
+    /// Those chances come from this player's beliefs, so cards that are dead
+    /// or believed to be elsewhere count for less.  A second copy of a card
+    /// adds only half its worth, as it only matters once the first is lost.
+    fn score_hand(&self, view: &GameView, hand: &[Card]) -> f64 {
+        hand.iter()
+            .enumerate()
+            .map(|(i, card)| {
+                let worth = self.action_value(*card) + self.defense_value(view, *card);
+
+                if hand[..i].contains(card) {
+                    worth/2.0
+                } else {
+                    worth
                 }
-            }
+            })
+            .sum()
+    }
 
-            return actions;
+    /// Gets the utility of the action a card lets this player take honestly.
+    fn action_value(&self, card: Card) -> f64 {
+        match card {
+            Card::Duke => self.utilities.tax,
+            Card::Captain => self.utilities.steal,
+            Card::Ambassador => self.utilities.exchange,
+            Card::Assassin => self.utilities.assassinate,
+            Card::Inquisitor => self.utilities.exchange.max(self.utilities.examine),
+            _ => 0.0,
         }
+    }
 
-        // Income and ForeignAid are always available.
-        actions.push(Action::Income);
-        actions.push(Action::ForeignAid);
+    /// Gets the utility of the attacks on this player a card blocks, each
+    /// weighted by the chance that some opponent holds the attacking card.
+    fn defense_value(&self, view: &GameView, card: Card) -> f64 {
+        // The chance that at least one opponent holds the given card
+        let threat = |attacker: Card| 1.0 - view.opponents().iter()
+            .map(|&i| 1.0 - self.beliefs.probability(i, attacker))
+            .product::<f64>();
+
+        let attacks = [
+            (Action::Assassinate (self.id), Card::Assassin, self.utilities.assassinate),
+            (Action::Steal (self.id), Card::Captain, self.utilities.steal),
+        ];
+
+        attacks.iter()
+            .filter(|(attack, _, _)| view.rules.blocking_cards(*attack).contains(&card))
+            .map(|(_, attacker, utility)| utility*threat(*attacker))
+            .sum()
+    }
 
-        // Coup is available (for any player that is not this one) if this player has
-        // at least 7 coins.
-        if self.coins >= 7 {
-            for i in 0..=self.opponents {
-                if i != self.id && !eliminated_players.contains(&i) {
-                    actions.push(Action::Coup (i));
-                }
+    /// Lists the cards this player has claimed since its hand last changed
+    /// in a way the table could see (a deal, an exchange, or a replaced or
+    /// swapped card).
+    fn claimed(&self, view: &GameView) -> Vec<Card> {
+        let mut claimed = Vec::new();
+
+        for event in view.history.iter().rev() {
+            match event {
+                GameEvent::Deal { player, .. }
+                | GameEvent::Exchange { player, .. }
+                | GameEvent::CardReplaced { player, .. }
+                | GameEvent::CardSwapped { player, .. } if *player == self.id => break,
+                GameEvent::ActionDeclared { player, action } if *player == self.id => {
+                    claimed.push(view.rules.required_card(*action));
+                },
+                GameEvent::Block { blocker, card, .. } if *blocker == self.id => {
+                    claimed.push(*card);
+                },
+                _ => (),
             }
         }
 
-        // For each card we have, we can take "safe" actions
+        claimed
+    }
 
-        // Dukes can Tax
-        if self.hand.contains(&Card::Duke) {
-            actions.push(Action::Tax);
+    /// Decides whether to act on a comparison of a belief to a cutoff, given
+    /// the margin by which the belief passes the cutoff in favor of acting.
+    /// 
+    /// At temperature 0, this acts exactly when the margin is positive.
+    /// Otherwise, it acts with a probability that rises smoothly from 0 to 1
+    /// with the margin, passing one half at the cutoff, and scaled by
+    /// `MARGIN_SCALE` so that one temperature softens both kinds of decision
+    /// about as much.
+    fn soft(&mut self, margin: f64) -> bool {
+        if self.temperature <= 0.0 {
+            return margin > 0.0;
         }
 
-        // Captains can Steal
-        if self.hand.contains(&Card::Captain) {
-            for i in 0..=self.opponents {
-                if i != self.id && !eliminated_players.contains(&i) {
-                    actions.push(Action::Steal (i));
-                }
-            }
-        }
+        let probability = 1.0/(1.0 + (-MARGIN_SCALE*margin/self.temperature).exp());
+        self.rng.gen::<f64>() < probability
+    }
 
-        // Ambassadors can Exchange
-        if self.hand.contains(&Card::Ambassador) {
-            actions.push(Action::Exchange);
-        }
+    /// Gets the chance that a claim is a lie above which challenging it is
+    /// worth more than letting it stand, or `None` if challenging it could
+    /// never be worth it.
+    /// 
+    /// Everything is measured in cards, with a coup's worth of coins as one
+    /// card.  A successful challenge is worth the claimant's lost card,
+    /// valued by the "liar" cutoff, plus whatever the claim would have cost
+    /// this player (see `challenge_stakes`).  A failed one costs this player
+    /// a card, and the last card costs the game.  The gain is discounted by
+    /// the chance that a player asked later, who has more at stake, will
+    /// challenge anyway: `lie` is the chance the claim is a lie.
+    /// 
+    /// With nothing at stake and two cards left, the threshold is one minus
+    /// the "liar" cutoff.
+    fn challenge_threshold(&self, view: &GameView, claimant: usize, lie: f64) -> Option<f64> {
+        let (stake, wrong) = self.challenge_stakes(view, claimant);
+
+        // The player with the most at stake: the target of the action, or
+        // the actor if the claim is a block
+        let exposed = if view.active_player == claimant {
+            view.current.and_then(|action| action.target())
+        } else {
+            Some (view.active_player)
+        };
 
-        // Assassins can Assassinate
-        if self.hand.contains(&Card::Assassin) && self.coins >= 3 {
-            for i in 0..=self.opponents {
-                if i != self.id && !eliminated_players.contains(&i) {
-                    actions.push(Action::Assassinate (i));
-                }
-            }
+        // Players are asked in turn order after the claimant, and only those
+        // after this player can challenge in its place
+        let players = view.players.len();
+        let later = (1..players)
+            .map(|i| (claimant + i) % players)
+            .skip_while(|&i| i != self.id)
+            .skip(1)
+            .any(|i| Some (i) == exposed && !view.players[i].is_eliminated());
+        let others = if later { lie } else { 0.0 };
+
+        // Scale everything by one minus the cutoff, so that a cutoff of 1
+        // (challenge anything that might be a lie) needs no special case
+        let cutoff = self.liar_cutoff.clamp(0.0, 1.0);
+        let gain = (cutoff + stake*(1.0 - cutoff))*(1.0 - others);
+        let loss = wrong*(1.0 - cutoff);
+
+        if gain > 0.0 {
+            Some (loss/(gain + loss))
+        } else {
+            None
         }
+    }
 
-        // Contessas can't take any actions :(
-
-        // Now we get to the lying: "dangerous" actions
-        // Which actions can I take that are deceptive but will not injure me seriously?
+    /// Gets what a claim would cost this player if it stood unchallenged,
+    /// and what challenging it would cost if the claim is true, both in
+    /// cards (see `challenge_threshold`).
+    /// 
+    /// Claims to take this player's coins or cards, or to block its action,
+    /// put the most at stake.  A failed challenge of an assassination of this
+    /// player costs it two cards rather than one, unless it can block with a
+    /// Contessa.
+    fn challenge_stakes(&self, view: &GameView, claimant: usize) -> (f64, f64) {
+        let rules = &view.rules;
+        let coins = |coins: u8| coins as f64/rules.coup_cost as f64;
+        let lost = self.lost_cost(view, 1);
+
+        if view.active_player != claimant {
+            // The claim blocks an action, which only matters to its actor
+            if view.active_player != self.id {
+                return (0.0, lost);
+            }
 
-        // Note: it's OK to use `Option::unwrap` here because we know we put each of these cards into the PerceivedHand hashmap
-        // earlier in the program
+            let stake = match view.current {
+                Some (Action::ForeignAid) => coins(rules.foreign_aid),
+                Some (Action::Assassinate (_)) => 1.0,
+                Some (Action::Steal (i)) => coins(rules.steal.min(view.players[i].coins)),
+                _ => 0.0,
+            };
 
-        // Dukes can Tax
-        if !self.hand.contains(&Card::Duke) && self.perceived_hands[self.id].get(&Card::Duke).unwrap() > &self.lying_cutoff {
-            actions.push(Action::Tax);
+            return (stake, lost);
         }
 
-        // Captains can Steal
-        if !self.hand.contains(&Card::Captain) && self.perceived_hands[self.id].get(&Card::Captain).unwrap() > &self.lying_cutoff {
-            for i in 0..=self.opponents {
-                if i != self.id && !eliminated_players.contains(&i) {
-                    actions.push(Action::Steal (i));
+        match view.current {
+            Some (Action::Assassinate (i)) if i == self.id => {
+                if self.hand.contains(&Card::Contessa) {
+                    (0.0, lost)
+                } else {
+                    (lost, self.lost_cost(view, 2) - lost)
                 }
-            }
+            },
+            Some (Action::Steal (i)) if i == self.id => (coins(rules.steal.min(view.players[self.id].coins)), lost),
+            Some (Action::Tax) => (coins(rules.tax)/view.opponents().len().max(1) as f64, lost),
+            _ => (0.0, lost),
         }
+    }
 
-        // Ambassadors can Exchange
-        if !self.hand.contains(&Card::Ambassador) && self.perceived_hands[self.id].get(&Card::Ambassador).unwrap() > &self.lying_cutoff {
-            actions.push(Action::Exchange);
+    /// Gets how believable a claim by this player to have a card would be:
+    /// certain if it has the card, and otherwise the chance the table gives
+    /// it of having the card, raised towards certainty by the consistency
+    /// weight if this player has claimed the card before.
+    fn block_credibility(&self, card: Card, claimed: &[Card]) -> f64 {
+        if self.hand.contains(&card) {
+            return 1.0;
         }
 
-        // Assassins can Assassinate
-        if !self.hand.contains(&Card::Assassin) && self.perceived_hands[self.id].get(&Card::Assassin).unwrap() > &self.lying_cutoff && self.coins >= 3 {
-            for i in 0..=self.opponents {
-                if i != self.id && !eliminated_players.contains(&i) {
-                    actions.push(Action::Assassinate (i));
-                }
-            }
+        // Note: it's OK to use `Option::unwrap` here because we know
+        // we put every card in play into our perceived hand earlier
+        let public = *self.perceived_hands[self.id].get(&card).unwrap();
+        if claimed.contains(&card) {
+            public + self.blocking.consistency.clamp(0.0, 1.0)*(1.0 - public)
+        } else {
+            public
         }
+    }
 
-        // Contessas can't take any actions :(
+    /// Gets the cost, in cards, of losing the given number of this player's
+    /// cards, where losing the last card costs `LAST_CARD`.
+    fn lost_cost(&self, view: &GameView, cards: usize) -> f64 {
+        let influence = view.players[self.id].influence();
 
-        actions
+        (0..cards.min(influence))
+            .map(|i| if i + 1 == influence { LAST_CARD } else { 1.0 })
+            .sum()
     }
 
-    /// Computes the utility of a given action according to a utility table.
-    fn compute_utility(&self, action: Action) -> f64 {
+    /// Scores an opponent as the target of an attack, by this player's
+    /// target weights.
+    /// 
+    /// The threat an opponent poses is the chance that it can attack this
+    /// player next turn: certain if it can afford a Coup, and otherwise the
+    /// chance it holds an Assassin it can pay for or a Captain.
+    fn target_score(&self, view: &GameView, action: Action, target: usize) -> f64 {
+        let opponent = &view.players[target];
+        let holds = |card: Card| self.beliefs.probability(target, card);
+
+        let coins = (opponent.coins as f64/view.rules.forced_coup as f64).min(1.0);
+        let influence = opponent.influence() as f64/2.0;
+        let threat = if opponent.coins >= view.rules.coup_cost {
+            1.0
+        } else {
+            let assassin = if opponent.coins >= view.rules.assassinate_cost { holds(Card::Assassin) } else { 0.0 };
+            1.0 - (1.0 - assassin)*(1.0 - holds(Card::Captain))
+        };
+        let blockers = 1.0 - view.rules.blocking_cards(action).iter()
+            .map(|&card| 1.0 - holds(card))
+            .product::<f64>();
+
+        self.targeting.coins*coins
+            + self.targeting.influence*influence
+            + self.targeting.threat*threat
+            + self.targeting.blockers*blockers
+    }
+
+    /// Scores an action by this player's utility model (see `UtilityModel`).
+    fn model_score(&self, view: &GameView, action: Action) -> f64 {
+        let weights = match self.model.get(action) {
+            Some (weights) => weights,
+            None => return 0.0,
+        };
+
+        let rules = &view.rules;
+        let opponents = view.opponents();
+        let target = action.target();
+        let share = |coins: u8| (coins as f64/rules.forced_coup as f64).min(1.0);
+
+        let coins = share(view.players[self.id].coins);
+        let influence = view.players[self.id].influence() as f64/2.0;
+        let (opponent_coins, opponent_influence) = match target {
+            Some (i) => (share(view.players[i].coins), view.players[i].influence() as f64/2.0),
+            None => (
+                opponents.iter().map(|&i| share(view.players[i].coins)).fold(0.0, f64::max),
+                opponents.iter().map(|&i| view.players[i].influence() as f64/2.0).sum::<f64>()/opponents.len().max(1) as f64,
+            ),
+        };
+
+        // Embezzling means claiming not to have a Duke
+        let honest = match action {
+            Action::Embezzle => !self.hand.contains(&Card::Duke),
+            _ => {
+                let card = rules.required_card(action);
+                card == Card::None || self.hand.contains(&card)
+            },
+        };
+
+        // The chance that at least one of the blockers holds a blocking card
+        let blockers = target.map_or(opponents, |i| vec![i]);
+        let blocked = 1.0 - rules.blocking_cards(action).iter()
+            .flat_map(|&card| blockers.iter().map(move |&i| (i, card)))
+            .map(|(i, card)| 1.0 - self.beliefs.probability(i, card))
+            .product::<f64>();
+
+        weights.coins*coins
+            + weights.influence*influence
+            + weights.opponent_coins*opponent_coins
+            + weights.opponent_influence*opponent_influence
+            + weights.honest*if honest { 1.0 } else { 0.0 }
+            + weights.blocked*blocked
+    }
+
+    /// Computes the utility of a given action according to a utility table,
+    /// adjusted to the state of the game by the utility model.
+    /// 
+    /// Attacks on an opponent are adjusted by how good a target that
+    /// opponent is (see `target_score`).
+    fn compute_utility(&self, view: &GameView, action: Action) -> f64 {
         let mut utility = match action {
             Action::Income => self.utilities.income,
             Action::ForeignAid => self.utilities.foreignaid,
@@ -495,7 +898,10 @@ impl Agent {
             Action::Exchange => self.utilities.exchange,
             Action::Steal (_) => self.utilities.steal,
             Action::Pass => 0.0,
-        };
+            Action::Convert (_) => self.utilities.convert,
+            Action::Embezzle => self.utilities.embezzle,
+            Action::Examine (_) => self.utilities.examine,
+        } + self.model_score(view, action);
 
         if action == Action::ForeignAid {
             for (i, hand) in self.perceived_hands.iter().enumerate() {
@@ -508,13 +914,17 @@ impl Agent {
             }
         } else if let Action::Steal (target) = action {
             // Note: it's OK to use `Option::unwrap` here because
-            // we know we put `Card::Captain` and `Card::Ambassador` in there earlier
+            // we know we put `Card::Captain` and the Exchange card in there earlier
             if self.perceived_hands[target].get(&Card::Captain).unwrap() > &self.liar_cutoff
-            || self.perceived_hands[target].get(&Card::Ambassador).unwrap() > &self.liar_cutoff {
+            || self.perceived_hands[target].get(&view.rules.exchange_card()).unwrap() > &self.liar_cutoff {
                 utility = 0.0;
             }
         }
 
+        if let Action::Coup (target) | Action::Assassinate (target) | Action::Steal (target) = action {
+            utility += self.target_score(view, action, target);
+        }
+
         utility
     }
 }
\ No newline at end of file
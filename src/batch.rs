@@ -0,0 +1,126 @@
+//! Implements running independent games across worker threads.
+
+use std::{
+    sync::{
+        Mutex,
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    },
+    thread,
+};
+
+use crate::{
+    Engine,
+    EngineError,
+};
+
+/// Runs batches of independent jobs, such as games, on worker threads.
+///
+/// Results always come back in the order of the jobs, and each job sees
+/// only its own index, so a batch whose jobs are seeded by their index
+/// gives the same results whatever the number of threads.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Batch {
+    threads: usize,
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implements the running of batches.
+impl Batch {
+    /// Constructs a batch runner with one thread per available core.
+    pub fn new() -> Self {
+        Self::with_threads(thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+
+    /// Constructs a batch runner with the given number of threads (at
+    /// least one).  With one thread, jobs run on the calling thread.
+    pub fn with_threads(threads: usize) -> Self {
+        Self {
+            threads: threads.max(1),
+        }
+    }
+
+    /// Gets the number of threads this runner uses.
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Runs `job` once for each index from 0 to `jobs`, and gets the results
+    /// in order.
+    ///
+    /// Workers take the next index as soon as they finish a job, so jobs of
+    /// uneven length are spread evenly.
+    pub fn run<T, F>(&self, jobs: usize, job: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize) -> T + Sync,
+    {
+        if self.threads == 1 || jobs < 2 {
+            return (0..jobs).map(job).collect();
+        }
+
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new((0..jobs).map(|_| None).collect::<Vec<Option<T>>>());
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(jobs) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= jobs {
+                        break;
+                    }
+
+                    let result = job(i);
+
+                    // Note: it's OK to use `Result::unwrap` here because no
+                    // thread holding this lock can panic
+                    results.lock().unwrap()[i] = Some (result);
+                });
+            }
+        });
+
+        // Note: it's OK to use `Option::unwrap` here because every index
+        // below `jobs` was taken by exactly one worker, which stored its
+        // result before the scope ended
+        results.into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.unwrap())
+            .collect()
+    }
+
+    /// Plays each of the given games until someone wins or it reaches
+    /// `RuleSet::max_turns`, and gets the finished engines and their
+    /// winners, in order.
+    ///
+    /// A game that reaches the cap has no winner (`Ok(None)`); a game in
+    /// which a player makes an illegal decision stops with the error.
+    pub fn play(&self, engines: Vec<Engine>) -> Vec<(Engine, Result<Option<usize>, EngineError>)> {
+        let engines = engines.into_iter()
+            .map(|engine| Mutex::new(Some (engine)))
+            .collect::<Vec<Mutex<Option<Engine>>>>();
+
+        self.run(engines.len(), |i| {
+            // Note: it's OK to use `Option::unwrap` here because each engine
+            // is taken by exactly one job
+            let mut engine = engines[i].lock().unwrap().take().unwrap();
+
+            let mut winner = Ok(None);
+            for _ in 0..engine.get_rules().max_turns {
+                winner = engine.turn(false);
+                if !matches!(winner, Ok(None)) {
+                    break;
+                }
+            }
+
+            (engine, winner)
+        })
+    }
+}
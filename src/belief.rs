@@ -0,0 +1,276 @@
+//! Tracks what a player believes about the hidden hands of the others.
+
+use crate::{
+    Card,
+    Action,
+    GameEvent,
+    GameView,
+    Distribution,
+};
+
+/// The likelihood of a player claiming a card they do not have, relative to
+/// claiming a card they do have.
+const BLUFF: f64 = 0.3;
+
+/// The likelihood of a player declining to block with a card they have,
+/// relative to declining without one.
+const PASS: f64 = 0.5;
+
+/// The likelihood of a player challenging a claim to a card they hold,
+/// relative to challenging a claim to a card they do not hold.
+const CHALLENGE: f64 = 1.5;
+
+/// Holds one player's beliefs about the hands of every player.
+///
+/// Each possible hand of each player is weighed by the likelihood of what
+/// that player has publicly done since they last drew unseen cards: a claim
+/// or a block is more likely with the card than without it, passing on a
+/// block is less likely with a blocking card, and challenges lost or won
+/// rule hands in or out.  The posterior probability of a hand multiplies
+/// this likelihood by the number of ways to draw that hand from the cards
+/// the believer has not seen.
+///
+/// The beliefs carry over from one decision to the next: `Belief::update`
+/// only reads the events added to `GameView::history` since it was last
+/// called.  Each player's hand is judged on its own, so two players may
+/// both be believed to hold the last copy of a card.  Beliefs about the
+/// believer's own hand are not meaningful; use `GameView::hand` instead.
+#[derive(Clone, Debug, Default)]
+pub struct Belief {
+    /// Holds the possible hands of each player, with the likelihood of each.
+    hands: Vec<Vec<(Vec<Card>, f64)>>,
+
+    /// Holds the cards the believer has not seen.
+    unseen: Distribution,
+
+    /// Counts the events of the history already taken into account.
+    seen: usize,
+}
+
+/// Implements commonly used functions performed on beliefs.
+impl Belief {
+    /// Constructs a new belief, with no knowledge of any game.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes into account every event that has occurred since the last update.
+    ///
+    /// A view of a different game (with a different number of players or a
+    /// shorter history) starts the beliefs over.
+    pub fn update(&mut self, view: &GameView) {
+        self.unseen = Distribution::private(view);
+
+        if self.hands.len() != view.players.len() || view.history.len() < self.seen {
+            self.hands = vec![self.possible(2); view.players.len()];
+            self.seen = 0;
+        }
+
+        for index in self.seen..view.history.len() {
+            self.observe(view, index);
+        }
+
+        self.seen = view.history.len();
+    }
+
+    /// Gets the probability that a player holds at least one copy of a card.
+    pub fn probability(&self, player: usize, card: Card) -> f64 {
+        self.hands(player).iter()
+            .filter(|(hand, _)| hand.contains(&card))
+            .map(|(_, probability)| probability)
+            .sum()
+    }
+
+    /// Lists the hands a player may hold, with the probability of each.
+    ///
+    /// If nothing the player has done is consistent with the cards left
+    /// unseen, the likelihoods are ignored and the hands are weighed by the
+    /// unseen cards alone.
+    pub fn hands(&self, player: usize) -> Vec<(Vec<Card>, f64)> {
+        let hands = match self.hands.get(player) {
+            Some (hands) => hands,
+            None => return Vec::new(),
+        };
+
+        let mut posterior = hands.iter()
+            .map(|(hand, likelihood)| (hand.clone(), self.unseen.ways(hand)*likelihood))
+            .collect::<Vec<(Vec<Card>, f64)>>();
+
+        if posterior.iter().all(|(_, weight)| *weight == 0.0) {
+            for (hand, weight) in posterior.iter_mut() {
+                *weight = self.unseen.ways(hand);
+            }
+        }
+
+        let total = posterior.iter().map(|(_, weight)| weight).sum::<f64>();
+        if total > 0.0 {
+            for (_, weight) in posterior.iter_mut() {
+                *weight /= total;
+            }
+        }
+
+        posterior
+    }
+
+    /// Updates the beliefs on the event at the given index of the history.
+    fn observe(&mut self, view: &GameView, index: usize) {
+        let rules = view.rules;
+
+        match view.history[index] {
+            GameEvent::ActionDeclared { player, action } => {
+                let claimed = rules.required_card(action);
+                let denied = rules.denied_card(action);
+
+                if claimed != Card::None {
+                    self.weigh(player, |hand| if hand.contains(&claimed) { 1.0 } else { BLUFF });
+                }
+
+                if denied != Card::None {
+                    self.weigh(player, |hand| if hand.contains(&denied) { BLUFF } else { 1.0 });
+                }
+            },
+            GameEvent::Block { blocker, card, .. } => {
+                self.weigh(blocker, |hand| if hand.contains(&card) { 1.0 } else { BLUFF });
+            },
+            GameEvent::Challenge { challenger, card, .. }
+            | GameEvent::BlockChallenged { challenger, card, .. } => {
+                // Holding copies of a card makes it more likely that
+                // somebody else is lying about it
+                self.weigh(challenger, |hand| if hand.contains(&card) { CHALLENGE } else { 1.0 });
+            },
+            GameEvent::ChallengeResolved { claimant, card, success, .. } => {
+                self.weigh(claimant, |hand| if hand.contains(&card) != success { 1.0 } else { 0.0 });
+            },
+            GameEvent::DenialResolved { claimant, card, success, .. } => {
+                self.weigh(claimant, |hand| if hand.contains(&card) == success { 1.0 } else { 0.0 });
+            },
+            GameEvent::CardReplaced { player, returned, .. } => self.redraw(player, returned),
+            GameEvent::InfluenceLost { player, card } => self.lose(player, card),
+            GameEvent::Exchange { player, .. } | GameEvent::CardSwapped { player, .. } => {
+                // The cards kept or drawn are unknown, so everything this
+                // player did before tells us nothing about their new hand
+                let size = self.size(player);
+                self.hands[player] = self.possible(size);
+            },
+            GameEvent::ActionPerformed { player, action } => {
+                // Players who could have blocked the action and did not
+                // probably do not have a blocking card
+                let blocked = view.history[..index].iter()
+                    .rev()
+                    .take_while(|event| !matches!(event, GameEvent::ActionDeclared { .. }))
+                    .any(|event| matches!(event, GameEvent::Block { .. }));
+                if blocked {
+                    return;
+                }
+
+                let passed = match action {
+                    Action::ForeignAid => (0..view.players.len())
+                        .filter(|&i| i != player && !view.allied(i, player))
+                        .collect(),
+                    Action::Assassinate (target) | Action::Steal (target) => vec![target],
+                    _ => Vec::new(),
+                };
+
+                let blocking = rules.blocking_cards(action);
+                for i in passed {
+                    self.weigh(i, |hand| if hand.iter().any(|card| blocking.contains(card)) { PASS } else { 1.0 });
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Multiplies the likelihood of each of a player's hands by the given
+    /// likelihood.
+    fn weigh<F: Fn(&[Card]) -> f64>(&mut self, player: usize, likelihood: F) {
+        let hands = match self.hands.get_mut(player) {
+            Some (hands) => hands,
+            None => return,
+        };
+
+        for (hand, weight) in hands.iter_mut() {
+            *weight *= likelihood(hand);
+        }
+
+        // Scale the likelihoods so the largest is 1, so that they do not
+        // vanish over a long game
+        let max = hands.iter().map(|(_, weight)| *weight).fold(0.0, f64::max);
+        if max > 0.0 {
+            for (_, weight) in hands.iter_mut() {
+                *weight /= max;
+            }
+        }
+    }
+
+    /// Updates a player's hands after they return a card to the deck and
+    /// draw an unseen card in its place.
+    fn redraw(&mut self, player: usize, returned: Card) {
+        self.weigh(player, |hand| if hand.contains(&returned) { 1.0 } else { 0.0 });
+
+        let size = self.size(player);
+        let old = self.hands[player].clone();
+        let likelihood = |hand: &[Card]| old.iter()
+            .find(|(h, _)| same(h, hand))
+            .map_or(0.0, |(_, weight)| *weight);
+
+        // Any card of the new hand may be the one drawn; the rest of the
+        // hand is what was kept alongside the returned card
+        self.hands[player] = self.possible(size).into_iter()
+            .map(|(hand, _)| {
+                let kept = (0..hand.len())
+                    .map(|i| {
+                        let mut before = hand.clone();
+                        before[i] = returned;
+                        likelihood(&before)
+                    })
+                    .sum::<f64>();
+
+                (hand, kept/size as f64)
+            })
+            .collect();
+    }
+
+    /// Updates a player's hands after they lose the given card.
+    fn lose(&mut self, player: usize, card: Card) {
+        if card == Card::None || player >= self.hands.len() {
+            return;
+        }
+
+        let size = self.size(player);
+        let hands = self.hands[player].iter()
+            .filter_map(|(hand, weight)| {
+                let i = hand.iter().position(|c| *c == card)?;
+                let mut hand = hand.clone();
+                hand.remove(i);
+                Some ((hand, *weight))
+            })
+            .collect::<Vec<(Vec<Card>, f64)>>();
+
+        self.hands[player] = if hands.is_empty() {
+            self.possible(size.saturating_sub(1))
+        } else {
+            hands
+        };
+    }
+
+    /// Gets the number of cards a player is believed to hold.
+    fn size(&self, player: usize) -> usize {
+        self.hands.get(player)
+            .and_then(|hands| hands.first())
+            .map_or(0, |(hand, _)| hand.len())
+    }
+
+    /// Lists every hand of the given size, each with a likelihood of 1.
+    fn possible(&self, size: usize) -> Vec<(Vec<Card>, f64)> {
+        self.unseen.combinations(size).into_iter()
+            .map(|hand| (hand, 1.0))
+            .collect()
+    }
+}
+
+/// Checks whether or not two hands hold the same cards.
+fn same(a: &[Card], b: &[Card]) -> bool {
+    a.len() == b.len() && a.iter().all(|card| {
+        a.iter().filter(|c| *c == card).count() == b.iter().filter(|c| *c == card).count()
+    })
+}
@@ -0,0 +1,935 @@
+//! Implements Monte Carlo Counterfactual Regret Minimization (MCCFR) over an
+//! abstraction of the game, and a player that plays the resulting strategy.
+//!
+//! The abstraction describes a decision by the kind of decision, the
+//! player's own hand, their coins (bucketed by what they can afford), the
+//! number of opponents left, the cards revealed so far, the claim or action
+//! being answered, and the cards recently claimed by the player(s) involved.
+//! Targets are not part of the abstraction: a targeted action is always
+//! taken against the opponent who looks most dangerous.
+
+use std::{
+    fmt,
+    fs,
+    io,
+    collections::HashMap,
+    path::Path,
+    str::FromStr,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use rand::{
+    Rng,
+    SeedableRng,
+    rngs::StdRng,
+    seq::SliceRandom,
+};
+
+use crate::{
+    Card,
+    Action,
+    Player,
+    PlayerMetadata,
+    GameEvent,
+    GameView,
+    Engine,
+    EngineError,
+    RuleSet,
+    search::{
+        value,
+        live,
+        replace,
+    },
+};
+
+/// The version of the strategy table format written by this crate.
+const VERSION: u32 = 1;
+
+/// The probability with which the training player explores an option
+/// uniformly at random instead of following its current strategy.
+const EXPLORATION: f64 = 0.6;
+
+/// The number of turns a training game may run before it is scored as it
+/// stands.
+const TRAINING_TURNS: usize = 100;
+
+/// The number of recent claims that are part of the abstraction.
+const RECENT: usize = 2;
+
+/// Holds a trained strategy: for each abstract information set, the
+/// probability of choosing each option.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct StrategyTable {
+    sets: HashMap<String, Vec<(String, f64)>>,
+}
+
+/// Enumerates the errors that can occur while saving or loading a strategy
+/// table.
+#[derive(Debug)]
+pub enum StrategyError {
+    /// The table could not be read or written.
+    Io (io::Error),
+
+    /// A line of the table could not be parsed.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for StrategyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrategyError::Io (e) => write!(f, "I/O error: {}", e),
+            StrategyError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for StrategyError {}
+
+impl From<io::Error> for StrategyError {
+    fn from(e: io::Error) -> Self {
+        StrategyError::Io (e)
+    }
+}
+
+/// Implements commonly used functions performed on strategy tables.
+impl StrategyTable {
+    /// Constructs an empty strategy table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the number of information sets in this table.
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    /// Checks whether or not this table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
+    /// Gets the options of the given information set and the probability of
+    /// choosing each one, if the set is in this table.
+    pub fn get(&self, key: &str) -> Option<&[(String, f64)]> {
+        self.sets.get(key).map(|options| options.as_slice())
+    }
+
+    /// Iterates over the information sets in this table.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<(String, f64)>)> {
+        self.sets.iter()
+    }
+
+    /// Saves this table to the given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), StrategyError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// Loads a table from the given file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, StrategyError> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl fmt::Display for StrategyTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Contessa strategy table")?;
+        writeln!(f, "strategy {}", VERSION)?;
+
+        let mut keys = self.sets.keys().collect::<Vec<&String>>();
+        keys.sort();
+
+        for key in keys {
+            write!(f, "set {}", key)?;
+            for (option, probability) in self.sets[key].iter() {
+                write!(f, " {}={}", option, probability)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for StrategyTable {
+    type Err = StrategyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sets = HashMap::new();
+
+        for (n, line) in s.lines().enumerate() {
+            let error = |message: &str| StrategyError::Parse {
+                line: n + 1,
+                message: message.to_string(),
+            };
+
+            // Strip comments
+            let line = match line.find('#') {
+                Some (i) => &line[..i],
+                None => line,
+            };
+
+            let tokens = line.split_whitespace().collect::<Vec<&str>>();
+            match tokens[..] {
+                [] => continue,
+                ["strategy", version] => {
+                    if version.parse::<u32>().ok() != Some (VERSION) {
+                        return Err(error("unsupported strategy version"));
+                    }
+                },
+                ["set", key, ref options @ ..] => {
+                    let options = options.iter()
+                        .map(|option| option.split_once('=')
+                            .and_then(|(name, p)| Some ((name.to_string(), p.parse::<f64>().ok()?)))
+                            .ok_or_else(|| error("expected `<option>=<probability>`")))
+                        .collect::<Result<Vec<(String, f64)>, StrategyError>>()?;
+                    if options.is_empty() {
+                        return Err(error("expected at least one option"));
+                    }
+                    sets.insert(key.to_string(), options);
+                },
+                _ => return Err(error("unknown line")),
+            }
+        }
+
+        Ok(Self {
+            sets,
+        })
+    }
+}
+
+/// Holds the regrets and the cumulative strategy of one information set.
+#[derive(Clone, Debug)]
+struct Node {
+    options: Vec<String>,
+    regrets: Vec<f64>,
+    average: Vec<f64>,
+}
+
+/// Implements commonly used functions performed on nodes.
+impl Node {
+    /// Constructs a node for the given options.
+    fn new(options: &[String]) -> Self {
+        Self {
+            options: options.to_vec(),
+            regrets: vec![0.0; options.len()],
+            average: vec![0.0; options.len()],
+        }
+    }
+
+    /// Constructs a node that starts out playing the given strategy.
+    fn from_strategy(options: &[String], strategy: &[(String, f64)]) -> Self {
+        let probabilities = options.iter()
+            .map(|option| strategy.iter().find(|(name, _)| name == option).map_or(0.0, |(_, p)| *p))
+            .collect::<Vec<f64>>();
+
+        Self {
+            options: options.to_vec(),
+            regrets: probabilities.clone(),
+            average: probabilities,
+        }
+    }
+
+    /// Gets the current strategy by regret matching: each option is chosen
+    /// in proportion to its positive regret, or uniformly if none is
+    /// positive.
+    fn current(&self) -> Vec<f64> {
+        normalize(self.regrets.iter().map(|r| r.max(0.0)).collect())
+    }
+
+    /// Gets the average strategy over every training game so far, which is
+    /// the one that converges.
+    fn average(&self) -> Vec<f64> {
+        normalize(self.average.clone())
+    }
+}
+
+/// Records one decision of the training player in a training game.
+#[derive(Clone, Debug)]
+struct Step {
+    key: String,
+    choice: usize,
+
+    /// The strategy of the information set when the decision was made.
+    current: Vec<f64>,
+
+    /// The probability with which the choice was sampled.
+    sampled: f64,
+}
+
+/// Holds the state of a training game, shared by every learning player.
+#[derive(Debug)]
+struct Session {
+    /// The information sets being trained.
+    nodes: HashMap<String, Node>,
+
+    /// The player whose regrets are updated after this game.
+    traverser: usize,
+
+    /// The strategy new information sets start out with, if not uniform.
+    prior: Option<Arc<StrategyTable>>,
+
+    /// Whether the traverser explores and records its decisions (`true`),
+    /// or plays its average strategy (`false`).
+    training: bool,
+
+    /// The decisions made by the traverser so far.
+    steps: Vec<Step>,
+}
+
+/// Enumerates where a `CfrAgent` gets its strategy.
+#[derive(Clone, Debug)]
+enum Source {
+    /// A finished strategy table.
+    Table (Arc<StrategyTable>),
+
+    /// The regrets of a training session.
+    Learning (Arc<Mutex<Session>>),
+}
+
+/// Holds the information and performs the actions of a player that plays a
+/// strategy trained by a `CfrTrainer`.
+///
+/// Actions, challenges, blocks, and influence losses are sampled from the
+/// strategy table; information sets that are not in the table are played
+/// uniformly at random.  Exchanges and the Inquisitor's decisions follow
+/// simple rules.
+#[derive(Clone, Debug)]
+pub struct CfrAgent {
+    /// Stores this player's ID.
+    id: usize,
+
+    /// Holds the player's hand.
+    hand: [Card; 2],
+
+    /// Provides the number of coins the player has.
+    coins: u8,
+
+    /// Provides the strategy this player follows.
+    source: Source,
+
+    /// Drives every random decision this player makes.
+    rng: StdRng,
+}
+
+impl Player for CfrAgent {
+    /// Gets the metadata of the given player.
+    fn get_metadata(&self) -> PlayerMetadata {
+        PlayerMetadata::Cfr
+    }
+
+    /// Checks whether or not a player has a given card.
+    fn check(&self, card: Card) -> bool {
+        self.hand.contains(&card)
+    }
+
+    /// Replaces one of the cards in this player's hand with the given card.
+    ///
+    /// Note: call this function only when you know for sure that the outgoing
+    /// card exists in this player's hand or you will mess things up.
+    fn replace(&mut self, current: Card, new: Card) {
+        replace(&mut self.hand, current, new);
+    }
+
+    /// Exchanges cards (used on `Ambassador`).
+    ///
+    /// Right now, this keeps the most valuable cards.
+    fn exchange(&mut self, _: &GameView, cards: &[Card]) -> Vec<Card> {
+        let mut options = live(&self.hand);
+        let influence = options.len();
+        options.extend_from_slice(cards);
+        options.sort_by_key(|card| std::cmp::Reverse(value(*card)));
+
+        let returned = options.split_off(influence);
+        self.hand = [Card::None, Card::None];
+        for (i, card) in options.into_iter().enumerate() {
+            self.hand[i] = card;
+        }
+
+        returned
+    }
+
+    /// Gets the number of coins this player has.
+    fn get_coins(&self) -> u8 {
+        self.coins
+    }
+
+    /// This player keeps no beliefs beyond what the abstraction sees.
+    fn compute_hands(&mut self, _: &GameView) {}
+
+    /// Deals the given cards to the player.
+    fn deal(&mut self, hand: [Card; 2]) {
+        self.hand = hand;
+    }
+
+    /// Chooses the first card of this player's hand.
+    ///
+    /// Right now, this picks the most valuable card.
+    fn choose_influence(&mut self, _: &GameView, cards: &[Card]) -> Card {
+        cards.iter()
+            .copied()
+            .max_by_key(|card| value(*card))
+            .unwrap_or(Card::None)
+    }
+
+    /// Gains the number of coins specified.
+    fn gain_coins(&mut self, coins: u8) {
+        self.coins += coins;
+    }
+
+    /// Loses the number of coins specified.
+    fn lose_coins(&mut self, coins: u8) -> u8 {
+        let lost = coins.min(self.coins);
+        self.coins -= lost;
+        lost
+    }
+
+    /// Forces the player to lose one influence.
+    fn lose_influence(&mut self, view: &GameView) -> Card {
+        let mut cards = live(&self.hand);
+        cards.sort_by_key(|card| order(view, *card));
+        cards.dedup();
+
+        let options = cards.iter().map(|card| card.to_string()).collect::<Vec<String>>();
+        let card = match cards.get(self.decide(view, "lose", "-".to_string(), None, &options)) {
+            Some (card) => *card,
+            None => Card::None,
+        };
+
+        replace(&mut self.hand, card, Card::None);
+        card
+    }
+
+    /// Asks this player if he challenges a claim.
+    ///
+    /// Returns `true` if the player challenges and `false` otherwise.
+    fn check_challenge(&mut self, view: &GameView, claimant: usize, card: Card) -> bool {
+        if card == Card::None || self.is_eliminated() {
+            return false;
+        }
+
+        // Tell apart claims to block, claims to act, and denials
+        let block = matches!(view.history.iter().rev().find(|event| matches!(event,
+            GameEvent::ActionDeclared { .. } | GameEvent::Block { .. })), Some (GameEvent::Block { .. }));
+        let context = if block {
+            format!("block{}", card)
+        } else if view.current == Some (Action::Embezzle) {
+            format!("not{}", card)
+        } else {
+            format!("{}{}", card, targeted(view))
+        };
+
+        let options = vec!["Pass".to_string(), "Challenge".to_string()];
+        self.decide(view, "challenge", context, Some (claimant), &options) == 1
+    }
+
+    /// Asks this player if he blocks an action.
+    ///
+    /// Returns `true` if the player blocks and `false` otherwise.  Also returns
+    /// the card with which the player blocks.
+    fn check_block(&mut self, view: &GameView, action: Action) -> (bool, Card) {
+        if self.is_eliminated() {
+            return (false, Card::None);
+        }
+
+        let cards = view.rules.blocking_cards(action);
+        let options = std::iter::once("None".to_string())
+            .chain(cards.iter().map(|card| card.to_string()))
+            .collect::<Vec<String>>();
+        let context = format!("{}{}", label(action, view.active_player), targeted(view));
+
+        match self.decide(view, "block", context, Some (view.active_player), &options) {
+            0 => (false, Card::None),
+            i => (true, cards[i - 1]),
+        }
+    }
+
+    /// Shows a card to a player examining this one.
+    ///
+    /// Right now, this shows the least valuable card.
+    fn show_card(&mut self, _: &GameView, _: usize) -> Card {
+        live(&self.hand).into_iter()
+            .min_by_key(|card| value(*card))
+            .unwrap_or(Card::None)
+    }
+
+    /// Decides whether or not to force an examined player to swap a card.
+    ///
+    /// Right now, this swaps away any card that can be used to take coins
+    /// or influence from other players.
+    fn check_examine(&mut self, _: &GameView, _: usize, card: Card) -> bool {
+        matches!(card, Card::Duke | Card::Assassin | Card::Captain)
+    }
+
+    /// Checks whether or not a player is still in the game.
+    fn is_eliminated(&self) -> bool {
+        self.hand == [Card::None, Card::None]
+    }
+
+    /// Select an action based on actions available.
+    fn select_action(&mut self, view: &GameView) -> Action {
+        let mut options = Vec::new();
+        for action in view.legal_actions.iter() {
+            let name = label(*action, view.id).to_string();
+            if !options.contains(&name) {
+                options.push(name);
+            }
+        }
+
+        let i = self.decide(view, "act", "-".to_string(), None, &options);
+        match options.get(i) {
+            Some (name) => concrete(view, name),
+            None => Action::Pass,
+        }
+    }
+
+    /// Reseeds this player's random number generator.
+    fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+/// Implements necessary behaviors of a player following a trained strategy.
+impl CfrAgent {
+    /// Constructs a new player with the given ID that follows the given
+    /// strategy table.
+    pub fn new(id: usize, table: Arc<StrategyTable>) -> Self {
+        Self::from_source(id, Source::Table (table), StdRng::from_entropy())
+    }
+
+    /// Constructs a new player whose decisions are derived from the given
+    /// seed.
+    pub fn with_seed(id: usize, table: Arc<StrategyTable>, seed: u64) -> Self {
+        Self::from_source(id, Source::Table (table), StdRng::seed_from_u64(seed))
+    }
+
+    /// Constructs a new player that plays from a training session.
+    ///
+    /// The engine reseeds the player before the game starts.
+    fn learning(id: usize, session: Arc<Mutex<Session>>) -> Self {
+        Self::from_source(id, Source::Learning (session), StdRng::seed_from_u64(0))
+    }
+
+    /// Constructs a new player from the given strategy source.
+    fn from_source(id: usize, source: Source, rng: StdRng) -> Self {
+        Self {
+            id,
+            hand: [Card::None, Card::None],
+            coins: 0,
+            source,
+            rng,
+        }
+    }
+
+    /// Picks one of the given options in the information set described by
+    /// the arguments, returning its index.
+    fn decide(&mut self, view: &GameView, kind: &str, context: String, claimant: Option<usize>, options: &[String]) -> usize {
+        if options.len() < 2 {
+            return 0;
+        }
+
+        let key = key(view, kind, context, claimant, options);
+
+        match &self.source {
+            Source::Table (table) => {
+                let weights = match table.get(&key) {
+                    Some (set) => options.iter()
+                        .map(|option| set.iter().find(|(name, _)| name == option).map_or(0.0, |(_, p)| *p))
+                        .collect(),
+                    None => vec![1.0; options.len()],
+                };
+                sample(&mut self.rng, &weights)
+            },
+            Source::Learning (session) => {
+                // Note: it's OK to use `Result::unwrap` here because no
+                // thread holding this lock can panic
+                let mut session = session.lock().unwrap();
+                let traverser = self.id == session.traverser;
+                let training = session.training;
+                let prior = session.prior.clone();
+                let node = session.nodes.entry(key.clone()).or_insert_with(|| match prior.as_ref().and_then(|table| table.get(&key)) {
+                    Some (strategy) => Node::from_strategy(options, strategy),
+                    None => Node::new(options),
+                });
+
+                if !traverser {
+                    return sample(&mut self.rng, &node.current());
+                }
+                if !training {
+                    return sample(&mut self.rng, &node.average());
+                }
+
+                // Explore, so that every option keeps being tried
+                let current = node.current();
+                let explore = current.iter()
+                    .map(|p| EXPLORATION/options.len() as f64 + (1.0 - EXPLORATION)*p)
+                    .collect::<Vec<f64>>();
+                let choice = sample(&mut self.rng, &explore);
+
+                session.steps.push(Step {
+                    key,
+                    choice,
+                    current,
+                    sampled: explore[choice],
+                });
+                choice
+            },
+        }
+    }
+}
+
+/// Trains a strategy for the abstracted game with outcome-sampling MCCFR.
+///
+/// Every training game is played by learning players that all follow the
+/// current strategy, except for one player (the traverser, taking turns
+/// between iterations) who also explores.  After the game, the traverser's
+/// regrets are updated along the path it played, weighted by how likely the
+/// path was to be sampled.  The average strategy is what converges, and is
+/// what `CfrTrainer::strategy` returns.
+///
+/// The abstraction forgets most of the game, so the trained strategy is not
+/// an equilibrium of the full game; `CfrTrainer::exploitability` estimates
+/// how far from one it is.
+#[derive(Clone, Debug)]
+pub struct CfrTrainer {
+    rules: RuleSet,
+    players: usize,
+    nodes: HashMap<String, Node>,
+    iterations: usize,
+    rng: StdRng,
+}
+
+/// Implements the training of a strategy.
+impl CfrTrainer {
+    /// Constructs a new trainer for games of the given number of players.
+    pub fn new(rules: RuleSet, players: usize) -> Self {
+        Self::from_rng(rules, players, StdRng::from_entropy())
+    }
+
+    /// Constructs a new trainer whose training is derived from the given
+    /// seed.
+    pub fn with_seed(rules: RuleSet, players: usize, seed: u64) -> Self {
+        Self::from_rng(rules, players, StdRng::seed_from_u64(seed))
+    }
+
+    /// Constructs a new trainer from the given random number generator.
+    fn from_rng(rules: RuleSet, players: usize, rng: StdRng) -> Self {
+        Self {
+            rules,
+            players,
+            nodes: HashMap::new(),
+            iterations: 0,
+            rng,
+        }
+    }
+
+    /// Gets the number of training games played so far.
+    pub fn get_iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Plays the given number of training games.
+    ///
+    /// Returns an error if the rules cannot be used with this many players.
+    pub fn train(&mut self, iterations: usize) -> Result<(), EngineError> {
+        for _ in 0..iterations {
+            let traverser = self.iterations % self.players;
+            let session = Arc::new(Mutex::new(Session {
+                nodes: std::mem::take(&mut self.nodes),
+                traverser,
+                prior: None,
+                training: true,
+                steps: Vec::new(),
+            }));
+
+            let players = (0..self.players)
+                .map(|i| Box::new(CfrAgent::learning(i, session.clone())) as Box<dyn Player>)
+                .collect();
+            let seed = self.rng.gen();
+            let reward = self.simulate(players, traverser, seed);
+
+            // Note: it's OK to use `Result::unwrap` here because no thread
+            // holding this lock can panic
+            let mut session = session.lock().unwrap();
+            let steps = std::mem::take(&mut session.steps);
+            self.nodes = std::mem::take(&mut session.nodes);
+
+            update(&mut self.nodes, &steps, reward?);
+            self.iterations += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the average strategy trained so far.
+    pub fn strategy(&self) -> StrategyTable {
+        let sets = self.nodes.iter()
+            .map(|(key, node)| {
+                let options = node.options.iter()
+                    .cloned()
+                    .zip(node.average())
+                    .collect();
+                (key.clone(), options)
+            })
+            .collect();
+
+        StrategyTable {
+            sets,
+        }
+    }
+
+    /// Estimates how much a player could gain by deviating from the trained
+    /// strategy, as a share of games won.
+    ///
+    /// For each seat, a best response to the strategy is trained with
+    /// `iterations` games against players who follow the strategy, starting
+    /// out from the strategy itself.  Then
+    /// `games` games are played with the response in that seat, and the
+    /// same games are played with the strategy in that seat; the estimate
+    /// is the average difference in reward.  The response is only
+    /// approximate, so this underestimates the true exploitability, but it
+    /// should shrink towards zero as training converges.
+    ///
+    /// Returns an error if the rules cannot be used with this many players.
+    pub fn exploitability(&mut self, iterations: usize, games: usize) -> Result<f64, EngineError> {
+        let table = Arc::new(self.strategy());
+        let count = self.players;
+        let mut gain = 0.0;
+
+        for seat in 0..self.players {
+            let session = Arc::new(Mutex::new(Session {
+                nodes: HashMap::new(),
+                traverser: seat,
+                prior: Some (table.clone()),
+                training: true,
+                steps: Vec::new(),
+            }));
+            let players = |response: bool| (0..count)
+                .map(|i| if i == seat && response {
+                    Box::new(CfrAgent::learning(i, session.clone())) as Box<dyn Player>
+                } else {
+                    Box::new(CfrAgent::with_seed(i, table.clone(), 0)) as Box<dyn Player>
+                })
+                .collect::<Vec<Box<dyn Player>>>();
+
+            for _ in 0..iterations {
+                let seed = self.rng.gen();
+                let reward = self.simulate(players(true), seat, seed)?;
+
+                // Note: it's OK to use `Result::unwrap` here because no
+                // thread holding this lock can panic
+                let mut session = session.lock().unwrap();
+                let steps = std::mem::take(&mut session.steps);
+                update(&mut session.nodes, &steps, reward);
+            }
+
+            session.lock().unwrap().training = false;
+
+            let mut difference = 0.0;
+            for _ in 0..games {
+                let seed = self.rng.gen();
+                difference += self.simulate(players(true), seat, seed)? - self.simulate(players(false), seat, seed)?;
+            }
+            gain += difference/games.max(1) as f64;
+        }
+
+        Ok(gain/self.players as f64)
+    }
+
+    /// Plays a game and gets the reward of the given player: 1 for a win, 0
+    /// for a loss, and an equal share among the players left standing if the
+    /// game runs too long.
+    fn simulate(&self, players: Vec<Box<dyn Player>>, player: usize, seed: u64) -> Result<f64, EngineError> {
+        let mut engine = Engine::with_seed(players, self.rules, seed)?;
+
+        let mut winner = None;
+        for _ in 0..TRAINING_TURNS {
+            winner = engine.turn(false)?;
+            if winner.is_some() {
+                break;
+            }
+        }
+
+        Ok(match winner {
+            Some (i) => if i == player { 1.0 } else { 0.0 },
+            None if engine.is_eliminated(player) => 0.0,
+            None => {
+                let left = (0..self.players).filter(|&i| !engine.is_eliminated(i)).count();
+                1.0/left as f64
+            },
+        })
+    }
+}
+
+/// Updates the regrets and the average strategy of every information set
+/// the traverser passed through in a game with the given reward.
+///
+/// This is outcome sampling: the value of the path is divided by the
+/// probability that the traverser sampled it, and each option's regret is
+/// the difference between its sampled value (zero for the options not
+/// taken) and the value of the strategy.
+fn update(nodes: &mut HashMap<String, Node>, steps: &[Step], reward: f64) {
+    // The traverser's reach and sampling probabilities before each step
+    let mut reach = Vec::with_capacity(steps.len());
+    let mut sampled = Vec::with_capacity(steps.len());
+    let (mut r, mut s) = (1.0, 1.0);
+    for step in steps {
+        reach.push(r);
+        sampled.push(s);
+        r *= step.current[step.choice];
+        s *= step.sampled;
+    }
+
+    let mut value = reward;
+    for (k, step) in steps.iter().enumerate().rev() {
+        let taken = value/step.sampled;
+        let estimate = step.current[step.choice]*taken;
+
+        if let Some (node) = nodes.get_mut(&step.key) {
+            for i in 0..node.regrets.len() {
+                let option = if i == step.choice { taken } else { 0.0 };
+                node.regrets[i] += (option - estimate)/sampled[k];
+                node.average[i] += reach[k]/sampled[k]*step.current[i];
+            }
+        }
+
+        value = estimate;
+    }
+}
+
+/// Gets the key of the abstract information set in which a player faces a
+/// decision.
+///
+/// `context` describes the claim or action being answered, and `claimant`
+/// is the player whose recent claims matter (every opponent if `None`).
+fn key(view: &GameView, kind: &str, context: String, claimant: Option<usize>, options: &[String]) -> String {
+    let names = |mut cards: Vec<Card>| {
+        if cards.is_empty() {
+            return "-".to_string();
+        }
+
+        cards.sort_by_key(|card| order(view, *card));
+        cards.iter().map(|card| card.to_string()).collect::<Vec<String>>().join(",")
+    };
+
+    let mut claims = view.history.iter()
+        .rev()
+        .filter_map(|event| match event {
+            GameEvent::ActionDeclared { player, action } => Some ((*player, view.rules.required_card(*action))),
+            GameEvent::Block { blocker, card, .. } => Some ((*blocker, *card)),
+            _ => None,
+        })
+        .filter(|&(player, card)| card != Card::None && player != view.id && claimant.is_none_or(|c| c == player))
+        .take(RECENT)
+        .map(|(_, card)| card)
+        .collect::<Vec<Card>>();
+    claims.sort_by_key(|card| order(view, *card));
+    claims.dedup();
+
+    // Which cards have been revealed matters more than how many copies
+    let mut revealed = view.killed();
+    revealed.sort_by_key(|card| order(view, *card));
+    revealed.dedup();
+
+    format!(
+        "{}/{}/c{}/o{}/{}/{}/{}/{}",
+        kind,
+        names(live(&view.hand)),
+        bucket(view.coins(), &view.rules),
+        view.opponents().len(),
+        names(revealed),
+        context,
+        names(claims),
+        options.join(","),
+    )
+}
+
+/// Buckets a number of coins by the most expensive action it affords.
+fn bucket(coins: u8, rules: &RuleSet) -> usize {
+    if coins >= rules.forced_coup {
+        3
+    } else if coins >= rules.coup_cost {
+        2
+    } else if coins >= rules.assassinate_cost {
+        1
+    } else {
+        0
+    }
+}
+
+/// Orders cards as the rules list them, so that hands are described the
+/// same way whatever order they are held in.
+fn order(view: &GameView, card: Card) -> usize {
+    view.rules.cards().iter().position(|c| *c == card).unwrap_or(usize::MAX)
+}
+
+/// Gets the name of an action in the abstraction, which leaves out its
+/// target (except whether a Convert targets the player taking it).
+fn label(action: Action, player: usize) -> &'static str {
+    match action {
+        Action::Income => "Income",
+        Action::ForeignAid => "ForeignAid",
+        Action::Coup (_) => "Coup",
+        Action::Tax => "Tax",
+        Action::Assassinate (_) => "Assassinate",
+        Action::Exchange => "Exchange",
+        Action::Steal (_) => "Steal",
+        Action::Pass => "Pass",
+        Action::Convert (i) if i == player => "ConvertSelf",
+        Action::Convert (_) => "Convert",
+        Action::Embezzle => "Embezzle",
+        Action::Examine (_) => "Examine",
+    }
+}
+
+/// Describes whether or not the action being answered targets the player
+/// answering it.
+fn targeted(view: &GameView) -> &'static str {
+    match view.current.and_then(|action| action.target()) {
+        Some (i) if i == view.id => "@me",
+        _ => "",
+    }
+}
+
+/// Turns an abstract action back into a legal action.
+///
+/// Steals target the richest opponent; every other targeted action targets
+/// the opponent with the most influence, then the most coins.
+fn concrete(view: &GameView, name: &str) -> Action {
+    view.legal_actions.iter()
+        .copied()
+        .filter(|action| label(*action, view.id) == name)
+        .max_by_key(|action| match (action, action.target()) {
+            (Action::Steal (_), Some (i)) => (view.players[i].coins as usize, view.players[i].influence()),
+            (_, Some (i)) => (view.players[i].influence(), view.players[i].coins as usize),
+            _ => (0, 0),
+        })
+        .unwrap_or(Action::Pass)
+}
+
+/// Normalizes non-negative weights into probabilities, or into a uniform
+/// distribution if they are all zero.
+fn normalize(weights: Vec<f64>) -> Vec<f64> {
+    let total = weights.iter().sum::<f64>();
+    if total > 0.0 && total.is_finite() {
+        weights.into_iter().map(|w| w/total).collect()
+    } else {
+        vec![1.0/weights.len() as f64; weights.len()]
+    }
+}
+
+/// Samples an index in proportion to the given weights, or uniformly if
+/// they cannot be sampled from.
+fn sample(rng: &mut StdRng, weights: &[f64]) -> usize {
+    let indices = (0..weights.len()).collect::<Vec<usize>>();
+    match indices.choose_weighted(rng, |&i| weights[i]) {
+        Ok(&i) => i,
+        Err(_) => rng.gen_range(0..weights.len().max(1)),
+    }
+}
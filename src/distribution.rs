@@ -0,0 +1,207 @@
+//! Computes the exact odds of every hidden hand from the cards left unseen.
+
+use crate::{
+    Card,
+    GameView,
+    RuleSet,
+};
+
+/// Holds the cards an observer has not seen, and how many of them each
+/// player holds.
+///
+/// Every unseen card is equally likely to be in any hidden position, whether
+/// in a player's hand or in the deck, so each player's hand is a draw from
+/// the unseen cards without replacement (a multivariate hypergeometric
+/// distribution).  This covers dead cards, players with one card left, and
+/// cards shuffled back into the deck, but not anything a player has done:
+/// see `Belief` for that.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Distribution {
+    /// Counts the unseen copies of each card in play.
+    unseen: Vec<(Card, usize)>,
+
+    /// Counts the hidden cards each player holds.
+    influence: Vec<usize>,
+}
+
+/// Implements commonly used functions performed on distributions.
+impl Distribution {
+    /// Constructs a new distribution.
+    ///
+    /// `seen` lists every card the observer has seen that is not in the deck
+    /// or a hidden hand, and `influence` gives the number of hidden cards
+    /// each player holds.
+    pub fn new(rules: &RuleSet, seen: &[Card], influence: Vec<usize>) -> Self {
+        let unseen = rules.cards().iter()
+            .map(|&card| {
+                let seen = seen.iter().filter(|c| **c == card).count();
+                (card, rules.copies.saturating_sub(seen))
+            })
+            .collect();
+
+        Self {
+            unseen,
+            influence,
+        }
+    }
+
+    /// Constructs the distribution seen by somebody who knows only the public
+    /// information in a view: the dead cards and each player's influence.
+    pub fn public(view: &GameView) -> Self {
+        let influence = view.players.iter()
+            .map(|player| player.influence())
+            .collect();
+
+        Self::new(&view.rules, &view.killed(), influence)
+    }
+
+    /// Constructs the distribution seen by the player a view was built for,
+    /// who also knows their own hand.
+    ///
+    /// The player's own hand is not hidden, so it is listed as empty.
+    pub fn private(view: &GameView) -> Self {
+        let mut seen = view.killed();
+        seen.extend(view.hand.iter().copied().filter(|card| *card != Card::None));
+
+        let influence = view.players.iter()
+            .enumerate()
+            .map(|(i, player)| if i == view.id { 0 } else { player.influence() })
+            .collect();
+
+        Self::new(&view.rules, &seen, influence)
+    }
+
+    /// Gets the number of unseen cards.
+    pub fn unseen(&self) -> usize {
+        self.unseen.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Gets the number of unseen copies of a card.
+    pub fn count(&self, card: Card) -> usize {
+        self.unseen.iter()
+            .find(|(c, _)| *c == card)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Gets the number of hidden cards a player holds.
+    pub fn influence(&self, player: usize) -> usize {
+        self.influence.get(player).copied().unwrap_or(0)
+    }
+
+    /// Counts the ways to draw the given hand from the unseen cards.
+    pub fn ways(&self, hand: &[Card]) -> f64 {
+        if hand.iter().any(|card| self.count(*card) == 0) {
+            return 0.0;
+        }
+
+        self.unseen.iter()
+            .map(|&(card, count)| choose(count, hand.iter().filter(|c| **c == card).count()))
+            .product()
+    }
+
+    /// Lists every hand of the given size that can be made of the cards in
+    /// play, whether or not it can be drawn from the unseen cards.
+    ///
+    /// Each hand lists its cards in the order of `RuleSet::cards`, and no two
+    /// hands hold the same cards.
+    pub fn combinations(&self, size: usize) -> Vec<Vec<Card>> {
+        let cards = self.unseen.iter()
+            .map(|(card, _)| *card)
+            .collect::<Vec<Card>>();
+        let mut hands = vec![Vec::new()];
+
+        for _ in 0..size {
+            hands = hands.into_iter()
+                .flat_map(|hand: Vec<Card>| {
+                    let start = hand.last()
+                        .and_then(|last| cards.iter().position(|c| c == last))
+                        .unwrap_or(0);
+
+                    cards[start..].iter().map(move |&card| {
+                        let mut hand = hand.clone();
+                        hand.push(card);
+                        hand
+                    })
+                })
+                .collect();
+        }
+
+        hands
+    }
+
+    /// Lists the hands a player may hold, with the probability of each.
+    ///
+    /// Hands that cannot be drawn from the unseen cards are left out, so the
+    /// list is empty if the player holds more cards than are unseen.
+    pub fn hands(&self, player: usize) -> Vec<(Vec<Card>, f64)> {
+        let size = self.influence(player);
+        let total = choose(self.unseen(), size);
+        if total == 0.0 {
+            return Vec::new();
+        }
+
+        self.combinations(size).into_iter()
+            .map(|hand| {
+                let ways = self.ways(&hand);
+                (hand, ways/total)
+            })
+            .filter(|(_, probability)| *probability > 0.0)
+            .collect()
+    }
+
+    /// Gets the probability that a player holds exactly `copies` copies of a
+    /// card.
+    pub fn copies(&self, player: usize, card: Card, copies: usize) -> f64 {
+        let size = self.influence(player);
+        let count = self.count(card);
+        let total = choose(self.unseen(), size);
+        if total == 0.0 || copies > size {
+            return 0.0;
+        }
+
+        choose(count, copies)*choose(self.unseen() - count, size - copies)/total
+    }
+
+    /// Gets the probability that a player holds at least one copy of a card.
+    pub fn probability(&self, player: usize, card: Card) -> f64 {
+        1.0 - self.copies(player, card, 0)
+    }
+
+    /// Lists the hands the given players may hold together, with the
+    /// probability of each combination.
+    ///
+    /// Unlike `Distribution::hands`, this accounts for the players drawing
+    /// from the same unseen cards: two players cannot both hold the last
+    /// copy of a card.  The number of combinations grows quickly with the
+    /// number of players.
+    pub fn joint(&self, players: &[usize]) -> Vec<(Vec<Vec<Card>>, f64)> {
+        let (player, rest) = match players.split_first() {
+            Some (split) => split,
+            None => return vec![(Vec::new(), 1.0)],
+        };
+
+        let mut joint = Vec::new();
+        for (hand, probability) in self.hands(*player) {
+            let mut remaining = self.clone();
+            for (card, count) in remaining.unseen.iter_mut() {
+                *count -= hand.iter().filter(|c| *c == card).count();
+            }
+
+            for (mut hands, rest_probability) in remaining.joint(rest) {
+                hands.insert(0, hand.clone());
+                joint.push((hands, probability*rest_probability));
+            }
+        }
+
+        joint
+    }
+}
+
+/// Computes the binomial coefficient `n` choose `k`.
+fn choose(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+
+    (0..k).fold(1.0, |total, i| total*(n - i) as f64/(i + 1) as f64)
+}
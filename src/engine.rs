@@ -1,75 +1,584 @@
 //! Provides a framework for a Coup engine.
 
-use rand::thread_rng;
-use rand::seq::SliceRandom;
+use std::fmt;
+
+use rand::{
+    Rng,
+    SeedableRng,
+    random,
+    rngs::StdRng,
+    seq::SliceRandom,
+};
 
 use crate::{
     Card,
     Action,
+    Faction,
     Player,
     PlayerMetadata,
+    GameEvent,
+    GameRecord,
+    Decision,
+    GameView,
+    PlayerState,
+    RuleSet,
 };
 
+/// Enumerates the errors that can occur while running a game.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum EngineError {
+    /// A player selected an action that the rules do not allow.
+    IllegalAction {
+        player: usize,
+        action: Action,
+    },
+
+    /// A player tried to block an action with a card that cannot block it.
+    IllegalBlock {
+        player: usize,
+        action: Action,
+        card: Card,
+    },
+
+    /// A player tried to give up a card that is not in that player's hand.
+    IllegalCard {
+        player: usize,
+        card: Card,
+    },
+
+    /// A player returned the wrong cards to the deck after an exchange.
+    IllegalExchange {
+        player: usize,
+    },
+
+    /// The rules do not allow a game with this many players.
+    InvalidPlayerCount {
+        players: usize,
+    },
+
+    /// The deck cannot deal every player a hand.
+    InvalidDeck {
+        players: usize,
+        cards: usize,
+    },
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::IllegalAction { player, action } => write!(f, "player {} cannot {}", player, action),
+            EngineError::IllegalBlock { player, action, card } => write!(f, "player {} cannot block {} with {}", player, action, card),
+            EngineError::IllegalCard { player, card } => write!(f, "player {} does not have {}", player, card),
+            EngineError::IllegalExchange { player } => write!(f, "player {} returned the wrong cards after exchanging", player),
+            EngineError::InvalidPlayerCount { players } => write!(f, "the rules do not allow a game with {} players", players),
+            EngineError::InvalidDeck { players, cards } => write!(f, "a deck of {} cards cannot deal {} players a hand", cards, players),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
 /// Holds the necessary information to run a Coup engine.
+/// 
+/// An engine can be cloned, players and all, to play a game ahead without
+/// disturbing it.
+#[derive(Clone)]
 pub struct Engine {
     deck: Vec<Card>,
     players: Vec<Box<dyn Player>>,
+    hands: Vec<[Card; 2]>,
+    revealed: Vec<Vec<Card>>,
     killed: Vec<Card>,
+    factions: Vec<Faction>,
+    reserve: u8,
     active_player: usize,
+    turn: usize,
+    current: Option<Action>,
+    rules: RuleSet,
+    seed: u64,
+    rng: StdRng,
+    events: Vec<GameEvent>,
+    record: GameRecord,
+    verbose: bool,
 }
 
 /// Implements the necessary behaviors for a Coup engine.
 impl Engine {
     /// Constructs a new engine with given parameters.
-    pub fn new(mut players: Vec<Box<dyn Player>>) -> Self {
-        let mut deck = vec![
-            Card::Duke,
-            Card::Duke,
-            Card::Duke,
-            Card::Captain,
-            Card::Captain,
-            Card::Captain,
-            Card::Ambassador,
-            Card::Ambassador,
-            Card::Ambassador,
-            Card::Assassin,
-            Card::Assassin,
-            Card::Assassin,
-            Card::Contessa,
-            Card::Contessa,
-            Card::Contessa,
-        ];
+    /// 
+    /// The game is seeded from system entropy; use `Engine::with_seed` to
+    /// play a reproducible game.
+    /// 
+    /// Returns an error if the rules cannot be used with this many players.
+    pub fn new(players: Vec<Box<dyn Player>>, rules: RuleSet) -> Result<Self, EngineError> {
+        Self::with_seed(players, rules, random())
+    }
+
+    /// Constructs a new engine whose shuffle, deal, and player decisions
+    /// are all derived from the given seed.
+    pub fn with_seed(players: Vec<Box<dyn Player>>, rules: RuleSet, seed: u64) -> Result<Self, EngineError> {
+        let mut deck = rules.deck();
 
         // Shuffle the deck.
-        deck.shuffle(&mut thread_rng());
+        deck.shuffle(&mut StdRng::seed_from_u64(seed));
 
-        // Deal the cards.
-        for (i, player) in players.iter_mut().enumerate() {
-            let hand = [deck[2*i], deck[2*i + 1]];
-            player.deal(hand);
+        Self::with_deck(players, rules, deck, seed)
+    }
+
+    /// Constructs a new engine that deals from the top of the given deck
+    /// without shuffling it first.
+    /// 
+    /// Every later shuffle and every player decision is derived from the
+    /// given seed.
+    /// 
+    /// Returns an error if there are fewer than two players (or, head to
+    /// head, anything but two), or if the deck cannot deal every player a
+    /// hand.
+    pub fn with_deck(mut players: Vec<Box<dyn Player>>, rules: RuleSet, deck: Vec<Card>, seed: u64) -> Result<Self, EngineError> {
+        if players.len() < 2 || (rules.head_to_head && players.len() != 2) {
+            return Err(EngineError::InvalidPlayerCount {
+                players: players.len(),
+            });
+        }
+
+        // Head to head, every player needs one of each card to choose from.
+        let too_small = if rules.head_to_head {
+            rules.cards().iter().any(|card| deck.iter().filter(|c| *c == card).count() < players.len())
+        } else {
+            deck.len() < 2*players.len()
+        };
+        if too_small || deck.contains(&Card::None) {
+            return Err(EngineError::InvalidDeck {
+                players: players.len(),
+                cards: deck.len(),
+            });
         }
-        deck.drain(0..2*players.len());
 
-        // Set up the active player.
-        let active_player = 0usize;
+        let mut rng = StdRng::seed_from_u64(seed);
 
-        // Set up a list of "killed" cards.
+        // Give each player its own RNG, derived from the game seed.
+        for player in players.iter_mut() {
+            player.reseed(rng.gen());
+        }
+
+        // Set up a list of "killed" cards, both overall and for each player.
         let killed = Vec::new();
+        let revealed = vec![Vec::new(); players.len()];
+
+        // Assign factions (used only with the Reformation expansion),
+        // alternating around the table.
+        let factions = (0..players.len())
+            .map(|i| if i % 2 == 0 { Faction::Loyalist } else { Faction::Reformist })
+            .collect();
+
+        let mut engine = Self {
+            deck: deck.clone(),
+            hands: vec![[Card::None, Card::None]; players.len()],
+            players,
+            revealed,
+            killed,
+            factions,
+            reserve: 0,
+            active_player: 0,
+            turn: 0,
+            current: None,
+            rules,
+            seed,
+            rng,
+            events: Vec::new(),
+            record: GameRecord::new(seed, rules, deck, Vec::new()),
+            verbose: false,
+        };
+
+        engine.deal()?;
+
+        Ok(engine)
+    }
+
+    /// Constructs an engine partway through a game, in the state described
+    /// by a view taken at the start of a turn, with the given hands dealt.
+    /// 
+    /// This is meant for simulating the rest of a game, as `Searcher` does.
+    /// Every player is given their coins from the view and their hand from
+    /// `hands`; the cards that are neither dead nor in a hand are shuffled
+    /// into the deck.  The history of the view becomes the start of the event
+    /// log, and the next call to `Engine::turn` plays turn `view.turn`.  The
+    /// record of the engine only holds the decisions made after this.
+    /// 
+    /// Returns an error if there is not one hand per player, or if the hands
+    /// hold cards that are not left in the game.
+    pub fn from_view(view: &GameView, mut players: Vec<Box<dyn Player>>, hands: Vec<[Card; 2]>, seed: u64) -> Result<Self, EngineError> {
+        if players.len() != view.players.len() || hands.len() != players.len() {
+            return Err(EngineError::InvalidPlayerCount {
+                players: players.len(),
+            });
+        }
+
+        // The deck holds every card that is not dead or in a hand
+        let mut deck = view.rules.deck();
+        let killed = view.killed();
+        for card in killed.iter().chain(hands.iter().flatten().filter(|c| **c != Card::None)) {
+            match deck.iter().position(|c| c == card) {
+                Some (i) => {
+                    deck.remove(i);
+                },
+                None => return Err(EngineError::InvalidDeck {
+                    players: players.len(),
+                    cards: view.rules.deck_size(),
+                }),
+            }
+        }
 
-        Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        deck.shuffle(&mut rng);
+
+        for (i, player) in players.iter_mut().enumerate() {
+            player.reseed(rng.gen());
+            player.deal(hands[i]);
+            player.gain_coins(view.players[i].coins);
+        }
+
+        let factions = view.players.iter()
+            .enumerate()
+            .map(|(i, p)| p.faction.unwrap_or(if i % 2 == 0 { Faction::Loyalist } else { Faction::Reformist }))
+            .collect();
+
+        Ok(Self {
             deck,
             players,
+            revealed: view.players.iter().map(|p| p.revealed.clone()).collect(),
             killed,
-            active_player,
+            factions,
+            reserve: view.reserve,
+            active_player: view.active_player,
+            turn: view.turn.saturating_sub(1),
+            current: None,
+            rules: view.rules,
+            seed,
+            rng,
+            events: view.history.clone(),
+            record: GameRecord::new(seed, view.rules, Vec::new(), hands.clone()),
+            hands,
+            verbose: false,
+        })
+    }
+
+    /// Deals every player a hand and hands out the starting coins.
+    /// 
+    /// Normally, each player is dealt two cards from the top of the deck.
+    /// Head to head, each player instead chooses one card from a set holding
+    /// one of each card; the other cards are shuffled back into the deck,
+    /// and each player is then dealt one more card.
+    fn deal(&mut self) -> Result<(), EngineError> {
+        let players = self.players.len();
+
+        if self.rules.head_to_head {
+            // Take one of each card (the first found from the top) for each player
+            let mut sets = Vec::new();
+            for _ in 0..players {
+                let mut set = Vec::new();
+                for card in self.rules.cards() {
+                    // Note: it's OK to use `Option::unwrap` here because the
+                    // constructor checked that there are enough copies of every card
+                    let i = self.deck.iter().position(|c| *c == card).unwrap();
+                    set.push(self.deck.remove(i));
+                }
+                sets.push(set);
+            }
+
+            for (i, set) in sets.into_iter().enumerate() {
+                let view = self.get_view(i);
+                let card = self.players[i].choose_influence(&view, &set);
+                self.record.push(Decision::Choose {
+                    player: i,
+                    card,
+                });
+
+                let kept = match set.iter().position(|c| *c == card) {
+                    Some (j) if card != Card::None => j,
+                    _ => return Err(EngineError::IllegalCard {
+                        player: i,
+                        card,
+                    }),
+                };
+
+                self.hands[i][0] = card;
+                self.deck.extend(set.iter().enumerate().filter(|(j, _)| *j != kept).map(|(_, c)| *c));
+            }
+
+            self.deck.shuffle(&mut self.rng);
+
+            for i in 0..players {
+                self.hands[i][1] = self.deck.remove(0);
+            }
+        } else {
+            for i in 0..players {
+                self.hands[i] = [self.deck[2*i], self.deck[2*i + 1]];
+            }
+            self.deck.drain(0..2*players);
+        }
+
+        for i in 0..players {
+            let hand = self.hands[i];
+            self.players[i].deal(hand);
+            self.events.push(GameEvent::Deal {
+                player: i,
+                hand,
+            });
         }
+        self.record.deals = self.hands.clone();
+
+        // Hand out the starting coins.  Head to head, the first player
+        // starts with one coin fewer.
+        for i in 0..players {
+            let coins = if self.rules.head_to_head && i == self.active_player {
+                self.rules.starting_coins.saturating_sub(1)
+            } else {
+                self.rules.starting_coins
+            };
+
+            self.players[i].gain_coins(coins);
+            self.events.push(GameEvent::CoinsMoved {
+                from: None,
+                to: Some (i),
+                amount: coins,
+            });
+        }
+
+        Ok(())
     }
 
-    /// Rotates the active player.
+    /// Gets the seed from which this game was generated.
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Gets the rules of this game.
+    pub fn get_rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    /// Gets every event that has occurred so far in this game, in order.
+    /// 
+    /// Note that this log contains private information (such as each player's
+    /// hand); use `GameEvent::public` to see what the table sees.
+    pub fn get_events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// Gets the record of every decision made so far in this game.
+    /// 
+    /// The record can be saved with `GameRecord::save` and replayed with
+    /// `GameRecord::engine_at`.
+    pub fn get_record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    /// Builds the view of the game that the given player is allowed to see.
+    pub fn get_view(&self, player: usize) -> GameView {
+        let players = self.players.iter()
+            .zip(self.revealed.iter())
+            .zip(self.factions.iter())
+            .map(|((p, revealed), faction)| PlayerState {
+                coins: p.get_coins(),
+                revealed: revealed.clone(),
+                faction: if self.rules.reformation { Some (*faction) } else { None },
+            })
+            .collect();
+
+        GameView {
+            id: player,
+            hand: self.hands[player],
+            players,
+            rules: self.rules,
+            reserve: self.reserve,
+            deck_size: self.deck.len(),
+            turn: self.turn,
+            active_player: self.active_player,
+            current: self.current,
+            legal_actions: self.legal_actions(player),
+            history: self.events.iter().map(|e| e.public()).collect(),
+        }
+    }
+
+    /// Lists every action the given player may legally declare.
+    /// 
+    /// This is the single source of truth for the rules governing which
+    /// actions are available: it does not depend on the player's hand, since
+    /// any player may claim any card.
+    /// 
+    /// With the Reformation expansion, players may not target members of
+    /// their own faction (see `Engine::allied`), and may only Embezzle when
+    /// there are coins in the Treasury Reserve.
+    pub fn legal_actions(&self, player: usize) -> Vec<Action> {
+        // If this player is eliminated, the only option is to Pass.
+        if self.is_eliminated(player) {
+            return vec![Action::Pass];
+        }
+
+        let coins = self.players[player].get_coins();
+        let targets = (0..self.players.len())
+            .filter(|&i| i != player && !self.is_eliminated(i) && !self.allied(player, i))
+            .collect::<Vec<usize>>();
+
+        // If this player has enough coins, the only option is to Coup.
+        if coins >= self.rules.forced_coup {
+            return targets.iter().map(|&i| Action::Coup (i)).collect();
+        }
+
+        let mut actions = vec![
+            Action::Income,
+            Action::ForeignAid,
+        ];
+
+        if coins >= self.rules.coup_cost {
+            actions.extend(targets.iter().map(|&i| Action::Coup (i)));
+        }
+
+        actions.push(Action::Tax);
+
+        if coins >= self.rules.assassinate_cost {
+            actions.extend(targets.iter().map(|&i| Action::Assassinate (i)));
+        }
+
+        actions.push(Action::Exchange);
+
+        if self.rules.inquisitor {
+            actions.extend(targets.iter().map(|&i| Action::Examine (i)));
+        }
+
+        actions.extend(targets.iter().map(|&i| Action::Steal (i)));
+
+        if self.rules.reformation {
+            actions.extend((0..self.players.len())
+                .filter(|&i| !self.is_eliminated(i) && coins >= self.convert_cost(player, i))
+                .map(Action::Convert));
+
+            if self.reserve > 0 {
+                actions.push(Action::Embezzle);
+            }
+        }
+
+        actions
+    }
+
+    /// Checks whether or not the given player may legally declare an action.
+    pub fn validate_action(&self, player: usize, action: Action) -> Result<(), EngineError> {
+        if self.legal_actions(player).contains(&action) {
+            Ok(())
+        } else {
+            Err(EngineError::IllegalAction {
+                player,
+                action,
+            })
+        }
+    }
+
+    /// Checks whether or not the given player is out of the game.
+    pub fn is_eliminated(&self, player: usize) -> bool {
+        self.hands[player] == [Card::None, Card::None]
+    }
+
+    /// Gets the number of coins a player pays to the treasury to declare an action.
+    /// 
+    /// The cost is paid when the action is declared.  It is refunded only if
+    /// the action is successfully challenged, and is lost if the action is blocked.
+    pub fn action_cost(&self, action: Action) -> u8 {
+        match action {
+            Action::Coup (_) => self.rules.coup_cost,
+            Action::Assassinate (_) => self.rules.assassinate_cost,
+            _ => 0,
+        }
+    }
+
+    /// Gets the number of coins a player pays into the Treasury Reserve to
+    /// Convert the given target.
+    pub fn convert_cost(&self, player: usize, target: usize) -> u8 {
+        if player == target {
+            self.rules.convert_cost
+        } else {
+            self.rules.convert_other_cost
+        }
+    }
+
+    /// Checks whether or not two players are protected from each other by
+    /// belonging to the same faction.
+    /// 
+    /// This is only ever the case with the Reformation expansion, and only
+    /// while some player left in the game belongs to the other faction.
+    pub fn allied(&self, a: usize, b: usize) -> bool {
+        self.rules.reformation
+            && self.factions[a] == self.factions[b]
+            && (0..self.players.len()).any(|i| !self.is_eliminated(i) && self.factions[i] != self.factions[a])
+    }
+
+    /// Checks whether or not a player may block an action taken by `actor`.
+    /// 
+    /// Any other player may block Foreign Aid, but only the target may
+    /// block an Assassination or a Steal.  Eliminated players cannot block,
+    /// and nobody may block a member of their own faction.
+    pub fn can_block(&self, player: usize, actor: usize, action: Action) -> bool {
+        if player == actor || self.is_eliminated(player) || self.allied(player, actor) {
+            return false;
+        }
+
+        match action {
+            Action::ForeignAid => true,
+            Action::Assassinate (target) => target == player,
+            Action::Steal (target) => target == player,
+            _ => false,
+        }
+    }
+
+    /// Checks whether or not a player may challenge a claim made by `claimant`.
+    /// 
+    /// Any player still in the game may challenge any claim but their own.
+    pub fn can_challenge(&self, player: usize, claimant: usize) -> bool {
+        player != claimant && !self.is_eliminated(player)
+    }
+
+    /// Lists the other players in turn order, starting after the given player.
+    fn others_in_turn_order(&self, player: usize) -> Vec<usize> {
+        (1..self.players.len())
+            .map(|k| (player + k) % self.players.len())
+            .collect()
+    }
+
+    /// Gets the winner of the game, if only one player is left standing.
+    pub fn get_winner(&self) -> Option<usize> {
+        let remaining = (0..self.players.len())
+            .filter(|&i| !self.is_eliminated(i))
+            .collect::<Vec<usize>>();
+
+        match remaining[..] {
+            [winner] => Some (winner),
+            _ => None,
+        }
+    }
+
+    /// Records an event in the game log.
+    /// 
+    /// In verbose mode, the public version of each event is also printed.
+    fn emit(&mut self, event: GameEvent) {
+        if self.verbose {
+            println!("{}", event.public());
+        }
+
+        self.events.push(event);
+    }
+
+    /// Rotates the active player, skipping players who are out of the game.
     pub fn rotate_active_player(&mut self) {
-        self.active_player += 1;
+        for _ in 0..self.players.len() {
+            self.active_player += 1;
 
-        if self.active_player == self.players.len() {
-            self.active_player = 0;
+            if self.active_player == self.players.len() {
+                self.active_player = 0;
+            }
+
+            if !self.is_eliminated(self.active_player) {
+                break;
+            }
         }
     }
 
@@ -78,234 +587,555 @@ impl Engine {
         &self.killed
     }
 
-    /// Asks each player (in turn) whether or not he challenges a claim.
+    /// Asks every player who may challenge a claim whether or not to challenge it.
     /// 
-    /// Returns `Some(i)`, where `i` is the ID of the first player to challenge
-    /// the claim, or `None` if nobody challenges.
-    fn check_challenges(&self, actor: usize, card: Card) -> Option<usize> {
-        for (i, player) in self.players.iter().enumerate() {
-            if i != actor && player.check_challenge(actor, card) && !player.is_eliminated() {
-                return Some(i);
+    /// All eligible players are asked before any answer is revealed, so their
+    /// responses are simultaneous.  If several players challenge, the first
+    /// one in turn order after the claimant is chosen.
+    /// 
+    /// Returns `Some(i)`, where `i` is the ID of the chosen challenger, or
+    /// `None` if nobody challenges.
+    fn check_challenges(&mut self, claimant: usize, card: Card) -> Option<usize> {
+        let mut challenger = None;
+
+        for i in self.others_in_turn_order(claimant) {
+            if self.can_challenge(i, claimant) {
+                let view = self.get_view(i);
+                let challenged = self.players[i].check_challenge(&view, claimant, card);
+                self.record.push(Decision::Challenge {
+                    player: i,
+                    challenged,
+                });
+
+                if challenged && challenger.is_none() {
+                    challenger = Some (i);
+                }
             }
         }
 
-        None
+        challenger
     }
 
-    /// Asks each player (in turn) whether or not he block an action.
+    /// Asks every player who may block an action whether or not to block it.
+    /// 
+    /// As with challenges, all eligible players answer simultaneously and the
+    /// first blocker in turn order after the actor is chosen.
     /// 
-    /// Returns `Some((i, card))`, where `i` is the ID of the first player to block
-    /// the action, or `None` if nobody blocks.
-    fn check_blocks(&self, actor: usize, action: Action) -> Option<(usize, Card)> {
-        for (i, player) in self.players.iter().enumerate() {
-            if i != actor {
-                let (chk, card) = player.check_block(action);
-                if chk && !player.is_eliminated() {
-                    return Some((i, card));
+    /// Returns `Some((i, card))`, where `i` is the ID of the chosen blocker,
+    /// or `None` if nobody blocks.  Returns an error if a player blocks with
+    /// a card that cannot block the action.
+    fn check_blocks(&mut self, actor: usize, action: Action) -> Result<Option<(usize, Card)>, EngineError> {
+        let mut blocker = None;
+
+        for i in self.others_in_turn_order(actor) {
+            if self.can_block(i, actor, action) {
+                let view = self.get_view(i);
+                let (chk, card) = self.players[i].check_block(&view, action);
+                self.record.push(Decision::Block {
+                    player: i,
+                    card: if chk { Some (card) } else { None },
+                });
+
+                if chk && !self.rules.blocking_cards(action).contains(&card) {
+                    return Err(EngineError::IllegalBlock {
+                        player: i,
+                        action,
+                        card,
+                    });
                 }
+
+                if chk && blocker.is_none() {
+                    blocker = Some ((i, card));
+                }
+            }
+        }
+
+        Ok(blocker)
+    }
+
+    /// Forces the given player to lose one influence.
+    /// 
+    /// The card is placed on the table (its value is public knowledge).
+    /// Nothing happens if the player is already out of the game.
+    fn lose_influence(&mut self, player: usize) -> Result<(), EngineError> {
+        if self.is_eliminated(player) {
+            return Ok(());
+        }
+
+        let view = self.get_view(player);
+        let lost = self.players[player].lose_influence(&view);
+        self.record.push(Decision::LoseInfluence {
+            player,
+            card: lost,
+        });
+
+        if lost == Card::None || !self.hands[player].contains(&lost) {
+            return Err(EngineError::IllegalCard {
+                player,
+                card: lost,
+            });
+        }
+
+        if self.hands[player][0] == lost {
+            self.hands[player][0] = Card::None;
+        } else {
+            self.hands[player][1] = Card::None;
+        }
+        self.revealed[player].push(lost);
+        self.killed.push(lost);
+        self.emit(GameEvent::InfluenceLost {
+            player,
+            card: lost,
+        });
+
+        if self.is_eliminated(player) {
+            self.emit(GameEvent::Eliminated {
+                player,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Resolves a challenge to a player's claim to have a given card.
+    /// 
+    /// If the claim is false, the claimant loses influence.  If the claim is
+    /// true, the claimant reveals the card, the challenger loses influence,
+    /// and the claimant shuffles the revealed card into the deck and draws
+    /// a replacement.
+    /// 
+    /// Returns `true` if the challenger was right (the claimant did not have
+    /// the card) and `false` otherwise.
+    fn resolve_challenge(&mut self, challenger: usize, claimant: usize, card: Card) -> Result<bool, EngineError> {
+        let success = !self.hands[claimant].contains(&card);
+
+        self.emit(GameEvent::ChallengeResolved {
+            challenger,
+            claimant,
+            card,
+            success,
+        });
+
+        if success {
+            // Claimant loses influence
+            self.lose_influence(claimant)?;
+        } else {
+            self.emit(GameEvent::CardRevealed {
+                player: claimant,
+                card,
+            });
+
+            // Challenger loses influence
+            self.lose_influence(challenger)?;
+
+            // Claimant shuffles the revealed card into the deck
+            // and draws a new card
+            self.deck.push(card);
+            self.deck.shuffle(&mut self.rng);
+            let drawn = self.deck.remove(0);
+            self.players[claimant].replace(card, drawn);
+
+            if self.hands[claimant][0] == card {
+                self.hands[claimant][0] = drawn;
+            } else {
+                self.hands[claimant][1] = drawn;
+            }
+
+            self.emit(GameEvent::CardReplaced {
+                player: claimant,
+                returned: card,
+                drawn,
+            });
+        }
+
+        Ok(success)
+    }
+
+    /// Resolves a challenge to a player's claim *not* to have a given card.
+    /// 
+    /// If the claimant has the card, the claimant loses influence.
+    /// Otherwise, the challenger loses influence, and the claimant shuffles
+    /// their hand into the deck and draws a new one.
+    /// 
+    /// Returns `true` if the challenger was right (the claimant had the
+    /// card) and `false` otherwise.
+    fn resolve_denial(&mut self, challenger: usize, claimant: usize, card: Card) -> Result<bool, EngineError> {
+        let success = self.hands[claimant].contains(&card);
+
+        self.emit(GameEvent::DenialResolved {
+            challenger,
+            claimant,
+            card,
+            success,
+        });
+
+        if success {
+            // Claimant loses influence
+            self.lose_influence(claimant)?;
+        } else {
+            // Challenger loses influence
+            self.lose_influence(challenger)?;
+
+            // Claimant shuffles their hand into the deck and draws a new one
+            let returned = self.hands[claimant].iter()
+                .copied()
+                .filter(|c| *c != Card::None)
+                .collect::<Vec<Card>>();
+            self.deck.extend_from_slice(&returned);
+            self.deck.shuffle(&mut self.rng);
+
+            for card in returned {
+                let drawn = self.deck.remove(0);
+                self.players[claimant].replace(card, drawn);
+
+                if self.hands[claimant][0] == card {
+                    self.hands[claimant][0] = drawn;
+                } else {
+                    self.hands[claimant][1] = drawn;
+                }
+
+                self.emit(GameEvent::CardReplaced {
+                    player: claimant,
+                    returned: card,
+                    drawn,
+                });
+            }
+        }
+
+        Ok(success)
+    }
+
+    /// Lets `examiner` look at one of the target's cards (chosen by the
+    /// target), and then decide whether or not to force the target to swap
+    /// it with the deck.
+    /// 
+    /// Nothing happens if the target is already out of the game.
+    fn examine(&mut self, examiner: usize, target: usize) -> Result<(), EngineError> {
+        if self.is_eliminated(target) {
+            return Ok(());
+        }
+
+        let view = self.get_view(target);
+        let card = self.players[target].show_card(&view, examiner);
+        self.record.push(Decision::Show {
+            player: target,
+            card,
+        });
+
+        if card == Card::None || !self.hands[target].contains(&card) {
+            return Err(EngineError::IllegalCard {
+                player: target,
+                card,
+            });
+        }
+
+        self.emit(GameEvent::CardShown {
+            player: target,
+            examiner,
+            card,
+        });
+
+        let view = self.get_view(examiner);
+        let swap = self.players[examiner].check_examine(&view, target, card);
+        self.record.push(Decision::Examine {
+            player: examiner,
+            swap,
+        });
+
+        if swap {
+            // The target shuffles the card into the deck and draws a new card
+            self.deck.push(card);
+            self.deck.shuffle(&mut self.rng);
+            let drawn = self.deck.remove(0);
+            self.players[target].replace(card, drawn);
+
+            if self.hands[target][0] == card {
+                self.hands[target][0] = drawn;
+            } else {
+                self.hands[target][1] = drawn;
             }
+
+            self.emit(GameEvent::CardSwapped {
+                player: target,
+                examiner,
+                returned: card,
+                drawn,
+            });
         }
 
-        None
+        Ok(())
+    }
+
+    /// Moves coins from the treasury to the given player.
+    fn gain_coins(&mut self, player: usize, coins: u8) {
+        self.players[player].gain_coins(coins);
+        self.emit(GameEvent::CoinsMoved {
+            from: None,
+            to: Some (player),
+            amount: coins,
+        });
+    }
+
+    /// Moves coins from the given player to the treasury.
+    fn pay_coins(&mut self, player: usize, coins: u8) {
+        let paid = self.players[player].lose_coins(coins);
+        self.emit(GameEvent::CoinsMoved {
+            from: Some (player),
+            to: None,
+            amount: paid,
+        });
     }
 
     /// Allows the active player to complete an action.
-    fn complete_action(&mut self, action: Action) {
+    /// 
+    /// The cost of the action has already been paid.
+    fn complete_action(&mut self, action: Action) -> Result<(), EngineError> {
         match action {
             Action::Income => {
-                self.players[self.active_player].gain_coins(1);
+                self.gain_coins(self.active_player, self.rules.income);
             },
             Action::ForeignAid => {
-                self.players[self.active_player].gain_coins(2);
+                self.gain_coins(self.active_player, self.rules.foreign_aid);
             },
             Action::Coup (target) => {
-                self.players[self.active_player].lose_coins(7);
-
                 // Instruct the target player to lose influence
-                // Place the card on the table (its value is public knowledge)
-                let lost = self.players[target].lose_influence();
-                if lost != Card::None {
-                    self.killed.push(lost);
-                }
+                self.lose_influence(target)?;
             },
             Action::Tax => {
-                self.players[self.active_player].gain_coins(3);
+                self.gain_coins(self.active_player, self.rules.tax);
             },
             Action::Assassinate (target) => {
-                self.players[self.active_player].lose_coins(3);
-
                 // Instruct the target player to lose influence
-                // Place the card on the table (its value is public knowledge)
-                let lost = self.players[target].lose_influence();
-                if lost != Card::None {
-                    self.killed.push(lost);
-                }
+                self.lose_influence(target)?;
             }
             Action::Exchange => {
-                let new = &self.deck[0..2];
-                let discarded = self.players[self.active_player].exchange(new);
-                self.deck.drain(0..2);
-                for card in discarded {
-                    self.deck.push(card);
+                let player = self.active_player;
+                let count = self.deck.len().min(self.rules.exchange_draw());
+                let drawn = self.deck.drain(0..count).collect::<Vec<Card>>();
+                let view = self.get_view(player);
+                let returned = self.players[player].exchange(&view, &drawn);
+                self.record.push(Decision::Exchange {
+                    player,
+                    returned: returned.clone(),
+                });
+
+                // The player keeps whichever cards were not returned
+                let mut pool = self.hands[player].to_vec();
+                pool.extend_from_slice(&drawn);
+                for card in returned.iter() {
+                    match pool.iter().position(|c| c == card) {
+                        Some (i) if *card != Card::None => {
+                            pool.remove(i);
+                        },
+                        _ => return Err(EngineError::IllegalExchange {
+                            player,
+                        }),
+                    }
                 }
+                if pool.len() != 2 {
+                    return Err(EngineError::IllegalExchange {
+                        player,
+                    });
+                }
+                self.hands[player] = [pool[0], pool[1]];
+
+                // The returned cards are shuffled back into the deck
+                self.deck.extend_from_slice(&returned);
+                self.deck.shuffle(&mut self.rng);
+
+                self.emit(GameEvent::Exchange {
+                    player,
+                    drawn,
+                    returned,
+                });
             },
             Action::Steal (target) => {
-                let stolen = self.players[target].lose_coins(2);
+                let stolen = self.players[target].lose_coins(self.rules.steal);
                 self.players[self.active_player].gain_coins(stolen);
+                self.emit(GameEvent::CoinsMoved {
+                    from: Some (target),
+                    to: Some (self.active_player),
+                    amount: stolen,
+                });
             },
             Action::Pass => (),
+            Action::Convert (target) => {
+                let player = self.active_player;
+                let cost = self.convert_cost(player, target);
+                let paid = self.players[player].lose_coins(cost);
+                self.reserve += paid;
+                self.emit(GameEvent::ReservePaid {
+                    player,
+                    amount: paid,
+                });
+
+                let faction = self.factions[target].other();
+                self.factions[target] = faction;
+                self.emit(GameEvent::Converted {
+                    player,
+                    target,
+                    faction,
+                });
+            },
+            Action::Examine (target) => {
+                self.examine(self.active_player, target)?;
+            },
+            Action::Embezzle => {
+                let amount = self.reserve;
+                self.reserve = 0;
+                self.players[self.active_player].gain_coins(amount);
+                self.emit(GameEvent::Embezzled {
+                    player: self.active_player,
+                    amount,
+                });
+            },
         };
+
+        Ok(())
     }
 
     /// Asks a player to claim an action, check challenges, check blocks, and then execute the action.
     ///
-    /// Returns `Some(i)` if Player `i` has won.
-    /// Returns `None` if no player has won.
-    pub fn turn(&mut self, verbose: bool) -> Option<usize> {
-        let mut eliminated_players = Vec::new();
+    /// Every step of the turn is recorded in the event log (see `Engine::get_events`);
+    /// in verbose mode, the public events are also printed as they occur.
+    ///
+    /// Returns `Ok(Some(i))` if Player `i` has won.
+    /// Returns `Ok(None)` if no player has won.
+    /// Returns an error if a player makes a decision that the rules do not
+    /// allow.  If the active player selects an illegal action, the state of
+    /// the game is left unchanged.
+    pub fn turn(&mut self, verbose: bool) -> Result<Option<usize>, EngineError> {
+        self.verbose = verbose;
+
+        // A finished game has no more turns
+        if let Some (winner) = self.get_winner() {
+            return Ok(Some(winner));
+        }
+
+        // Players who are out of the game do not take turns
+        if self.is_eliminated(self.active_player) {
+            self.rotate_active_player();
+        }
+
+        self.record.start_turn();
+        self.turn += 1;
 
         // Work out probabilities
-        for (i, player) in self.players.iter_mut().enumerate() {
-            player.compute_hands(&self.killed);
-            if player.is_eliminated() {
-                eliminated_players.push(i);
-            }
+        for i in 0..self.players.len() {
+            let view = self.get_view(i);
+            self.players[i].compute_hands(&view);
         }
 
         // Ask the active player to select an action
-        let action = self.players[self.active_player].select_action(&eliminated_players);
+        let actor = self.active_player;
+        let view = self.get_view(actor);
+        let action = self.players[actor].select_action(&view);
+        if let Err(e) = self.validate_action(actor, action) {
+            self.record.turns.pop();
+            self.turn -= 1;
+            return Err(e);
+        }
+        self.record.push(Decision::Action {
+            player: actor,
+            action,
+        });
 
-        if verbose {
-            println!("Player {} selects {}", self.active_player, action);
+        self.emit(GameEvent::ActionDeclared {
+            player: actor,
+            action,
+        });
+        self.current = Some (action);
+
+        // Pay for the action up front
+        let cost = self.action_cost(action);
+        if cost > 0 {
+            self.pay_coins(actor, cost);
         }
 
         // Has the active player been somehow prevented from completing the action?
         let mut prevented = false;
 
-        // Determine the corresponding card.
-        let card = match action {
-            Action::Income => Card::None,
-            Action::ForeignAid => Card::None,
-            Action::Coup (_) => Card::None,
-            Action::Tax => Card::Duke,
-            Action::Assassinate (_) => Card::Assassin,
-            Action::Exchange => Card::Ambassador,
-            Action::Steal (_) => Card::Captain,
-            Action::Pass => Card::None,
+        // Check challenges, either to a claim to have a card or (for
+        // Embezzle) to a claim not to have one
+        let card = self.rules.required_card(action);
+        let denied = self.rules.denied_card(action);
+        let challenger = if card != Card::None {
+            self.check_challenges(actor, card)
+        } else if denied != Card::None {
+            self.check_challenges(actor, denied)
+        } else {
+            None
         };
 
-        // Check challenges
-        let challenger = self.check_challenges(self.active_player, card);
-
-        match challenger {
-            Some (i) => {
-                if verbose {
-                    println!("Player {} challenges {}", i, action);
-                }
-
-                if self.players[self.active_player].check(card) {
-                    // Challenger loses influence
-                    let killed = self.players[i].lose_influence();
-                    if verbose {
-                        println!("Player {} loses {}", i, killed);
-                    }
-                    if killed != Card::None {
-                        self.killed.push(killed);
-                    }
-
-                    // Active player adds his card to the bottom of the deck
-                    // and draws a new card
-                    self.deck.push(card);
-                    self.players[self.active_player].replace(card, self.deck[0]);
-                    self.deck.drain(0..1);
-                } else {
-                    // Active player loses influence
-                    let killed = self.players[self.active_player].lose_influence();
-                    if verbose {
-                        println!("Player {} loses {}", self.active_player, killed);
-                    }
-                    if killed != Card::None {
-                        self.killed.push(killed);
-                    }
+        if let Some (i) = challenger {
+            // If the challenge succeeds, the active player does not complete
+            // the action and the cost of the action is refunded
+            if denied != Card::None {
+                self.emit(GameEvent::DenialChallenge {
+                    challenger: i,
+                    claimant: actor,
+                    card: denied,
+                });
+                prevented = self.resolve_denial(i, actor, denied)?;
+            } else {
+                self.emit(GameEvent::Challenge {
+                    challenger: i,
+                    claimant: actor,
+                    card,
+                });
+                prevented = self.resolve_challenge(i, actor, card)?;
+            }
 
-                    // The active player does not complete the action
-                    prevented = true;
-                }
-            },
-            None => {},
+            if prevented && cost > 0 {
+                self.gain_coins(actor, cost);
+            }
         }
 
         if !prevented {
             // Check blocks
-            let block = self.check_blocks(self.active_player, action);
-
-            match block {
-                Some ((i, card)) => {
-                    // Player I blocks
-    
-                    if verbose {
-                        println!("Player {} blocks {}", i, action);
-                    }
-    
-                    // Check challenges to the block
-                    let challenger = self.check_challenges(i, card);
-    
-                    match challenger {
-                        Some (j) => {
-                            // Player J challenges the block
-    
-                            if verbose {
-                                println!("Player {} challenges Player {}", j, i);
-                            }
-    
-                            // Check if Player I actually has the card
-                            if self.players[i].check(card) {
-                                // Player J loses influence
-                                let killed = self.players[j].lose_influence();
-                                if verbose {
-                                    println!("Player {} loses {}", j, killed);
-                                }
-                                if killed != Card::None {
-                                    self.killed.push(killed);
-                                }
-    
-                                // Player I adds his card to the bottom of the deck
-                                // and draws a new card
-                                self.deck.push(card);
-                                self.players[i].replace(card, self.deck[0]);
-                                self.deck.drain(0..1);
-
-                                // The active player does not complete the action
-                                prevented = true;
-                            } else {
-                                // Player I loses influence
-                                let killed = self.players[i].lose_influence();
-                                if verbose {
-                                    println!("Player {} loses {}", i, killed);
-                                }
-                                if killed != Card::None {
-                                    self.killed.push(killed);
-                                }
-    
-                                // The active player does complete the action
-                            }
-                        },
-                        // If nobody challenges, the block is in effect
-                        None => prevented = true,
-                    }
-                },
-                None => {},
+            let block = self.check_blocks(actor, action)?;
+
+            if let Some ((i, card)) = block {
+                // Player I blocks
+                self.emit(GameEvent::Block {
+                    blocker: i,
+                    actor,
+                    action,
+                    card,
+                });
+
+                // Check challenges to the block
+                let challenger = self.check_challenges(i, card);
+
+                match challenger {
+                    Some (j) => {
+                        // Player J challenges the block
+                        self.emit(GameEvent::BlockChallenged {
+                            challenger: j,
+                            blocker: i,
+                            card,
+                        });
+
+                        // If Player I actually has the card, the active player
+                        // does not complete the action
+                        prevented = !self.resolve_challenge(j, i, card)?;
+                    },
+                    // If nobody challenges, the block is in effect
+                    None => prevented = true,
+                }
             }
         }
 
         if !prevented {
             // The active player completes the action
-            if verbose {
-                println!("Player {} performs {}", self.active_player, action);
-            }
-            self.complete_action(action);
+            self.emit(GameEvent::ActionPerformed {
+                player: actor,
+                action,
+            });
+            self.complete_action(action)?;
         }
 
+        self.current = None;
         self.rotate_active_player();
 
         if verbose {
@@ -314,7 +1144,15 @@ impl Engine {
             for (i, player) in self.players.iter().enumerate() {
                 println!("Player {}", i);
                 println!("Coins: {}", player.get_coins());
-                println!("Eliminated: {}", player.is_eliminated());
+                if self.rules.reformation {
+                    println!("Faction: {}", self.factions[i]);
+                }
+                println!("Eliminated: {}", self.is_eliminated(i));
+                println!();
+            }
+
+            if self.rules.reformation {
+                println!("Treasury Reserve: {}", self.reserve);
                 println!();
             }
 
@@ -323,40 +1161,33 @@ impl Engine {
             println!();
         }
 
-        if eliminated_players.len() == self.players.len() - 1 {
-            for i in 0..self.players.len() {
-                if !eliminated_players.contains(&i) {
-                    return Some(i);
-                }
-            }
+        // Check whether only one player is left standing
+        if let Some (winner) = self.get_winner() {
+            self.emit(GameEvent::Win {
+                player: winner,
+            });
+            return Ok(Some(winner));
         }
 
-        None
+        Ok(None)
     }
 
     /// Play a game and return the data of the player who won.
     /// 
-    /// Caps a game at 1000 turns.  If 1000 turns are reached,
-    /// Player 0 wins by default.
-    pub fn play(&mut self, verbose: bool) -> PlayerMetadata {
-        let mut option: Option<usize> = None;
-
-        let mut counter = 0;
-
-        loop {
-            if let Some(player) = option {
-                if verbose {
-                    println!("Player {} wins!", player);
-                }
-                return self.players[player].get_metadata();
-            } else {
-                option = self.turn(verbose);
-                counter += 1;
-            }
-
-            if counter == 1000 {
-                return self.players[0].get_metadata();
+    /// Caps a game at `RuleSet::max_turns` turns.  If the cap is reached,
+    /// Player 0 wins by default: this returns a single winner, so it cannot
+    /// share the game among the players left standing the way training and
+    /// tournaments do (they call `Engine::turn` themselves for that reason).
+    /// 
+    /// Returns an error if any player makes an illegal decision.
+    pub fn play(&mut self, verbose: bool) -> Result<PlayerMetadata, EngineError> {
+        // A win on the last turn allowed still counts
+        for _ in 0..self.rules.max_turns {
+            if let Some (player) = self.turn(verbose)? {
+                return Ok(self.players[player].get_metadata());
             }
         }
+
+        Ok(self.players[0].get_metadata())
     }
-}
\ No newline at end of file
+}
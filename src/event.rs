@@ -0,0 +1,265 @@
+//! Provides a structured log of everything that happens in a game.
+
+use std::fmt;
+
+use crate::{
+    Card,
+    Action,
+    Faction,
+};
+
+/// Enumerates the events that can occur over the course of a game.
+///
+/// Some events carry private information (for example, the cards dealt to
+/// a player).  Use `GameEvent::public` to obtain the version of an event
+/// that every player at the table is allowed to see.
+#[derive(Hash, PartialEq, Eq, Clone, Debug)]
+pub enum GameEvent {
+    /// A player is dealt a starting hand.
+    Deal {
+        player: usize,
+        hand: [Card; 2],
+    },
+
+    /// A player declares an action.
+    ActionDeclared {
+        player: usize,
+        action: Action,
+    },
+
+    /// A player challenges another player's claim to have a card.
+    Challenge {
+        challenger: usize,
+        claimant: usize,
+        card: Card,
+    },
+
+    /// A challenge is resolved.
+    ///
+    /// `success` is `true` if the challenger was right (the claimant did not
+    /// have the card) and `false` otherwise.
+    ChallengeResolved {
+        challenger: usize,
+        claimant: usize,
+        card: Card,
+        success: bool,
+    },
+
+    /// A player challenges another player's claim *not* to have a card
+    /// (Reformation's Embezzle).
+    DenialChallenge {
+        challenger: usize,
+        claimant: usize,
+        card: Card,
+    },
+
+    /// A challenge to a claim not to have a card is resolved.
+    ///
+    /// `success` is `true` if the challenger was right (the claimant did
+    /// have the card) and `false` otherwise.
+    DenialResolved {
+        challenger: usize,
+        claimant: usize,
+        card: Card,
+        success: bool,
+    },
+
+    /// A player reveals a card to prove a claim.
+    CardRevealed {
+        player: usize,
+        card: Card,
+    },
+
+    /// A player returns a revealed card to the deck and draws a replacement.
+    CardReplaced {
+        player: usize,
+        returned: Card,
+        drawn: Card,
+    },
+
+    /// A player shows one of their cards to the player examining them.
+    CardShown {
+        player: usize,
+        examiner: usize,
+        card: Card,
+    },
+
+    /// A player is forced by their examiner to swap the card they showed
+    /// with the deck.
+    CardSwapped {
+        player: usize,
+        examiner: usize,
+        returned: Card,
+        drawn: Card,
+    },
+
+    /// A player blocks an action.
+    Block {
+        blocker: usize,
+        actor: usize,
+        action: Action,
+        card: Card,
+    },
+
+    /// A player challenges a block.
+    BlockChallenged {
+        challenger: usize,
+        blocker: usize,
+        card: Card,
+    },
+
+    /// A player loses one influence, turning the given card face up.
+    InfluenceLost {
+        player: usize,
+        card: Card,
+    },
+
+    /// Coins move between players or between a player and the treasury.
+    ///
+    /// `None` stands for the treasury.
+    CoinsMoved {
+        from: Option<usize>,
+        to: Option<usize>,
+        amount: u8,
+    },
+
+    /// A player draws cards from the deck and returns the same number.
+    Exchange {
+        player: usize,
+        drawn: Vec<Card>,
+        returned: Vec<Card>,
+    },
+
+    /// A player pays coins into the Treasury Reserve.
+    ReservePaid {
+        player: usize,
+        amount: u8,
+    },
+
+    /// A player takes every coin in the Treasury Reserve.
+    Embezzled {
+        player: usize,
+        amount: u8,
+    },
+
+    /// A player changes the faction of `target`, who may be themselves.
+    Converted {
+        player: usize,
+        target: usize,
+        faction: Faction,
+    },
+
+    /// A player completes an action that was not prevented.
+    ActionPerformed {
+        player: usize,
+        action: Action,
+    },
+
+    /// A player loses the last of their influence.
+    Eliminated {
+        player: usize,
+    },
+
+    /// A player wins the game.
+    Win {
+        player: usize,
+    },
+}
+
+/// Implements commonly used functions performed on game events.
+impl GameEvent {
+    /// Returns the version of this event that every player may see.
+    ///
+    /// Cards that are known only to one player are replaced by `Card::None`.
+    pub fn public(&self) -> Self {
+        match self {
+            GameEvent::Deal { player, .. } => GameEvent::Deal {
+                player: *player,
+                hand: [Card::None, Card::None],
+            },
+            GameEvent::CardReplaced { player, returned, .. } => GameEvent::CardReplaced {
+                player: *player,
+                returned: *returned,
+                drawn: Card::None,
+            },
+            GameEvent::Exchange { player, drawn, returned } => GameEvent::Exchange {
+                player: *player,
+                drawn: vec![Card::None; drawn.len()],
+                returned: vec![Card::None; returned.len()],
+            },
+            GameEvent::CardShown { player, examiner, .. } => GameEvent::CardShown {
+                player: *player,
+                examiner: *examiner,
+                card: Card::None,
+            },
+            GameEvent::CardSwapped { player, examiner, .. } => GameEvent::CardSwapped {
+                player: *player,
+                examiner: *examiner,
+                returned: Card::None,
+                drawn: Card::None,
+            },
+            event => event.clone(),
+        }
+    }
+}
+
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameEvent::Deal { player, hand } => if hand.contains(&Card::None) {
+                write!(f, "Player {} is dealt two cards", player)
+            } else {
+                write!(f, "Player {} is dealt {} and {}", player, hand[0], hand[1])
+            },
+            GameEvent::ActionDeclared { player, action } => write!(f, "Player {} selects {}", player, action),
+            GameEvent::Challenge { challenger, claimant, card } => write!(f, "Player {} challenges Player {}'s claim to have {}", challenger, claimant, card),
+            GameEvent::ChallengeResolved { claimant, card, success, .. } => if *success {
+                write!(f, "Player {} does not have {}", claimant, card)
+            } else {
+                write!(f, "Player {} has {}", claimant, card)
+            },
+            GameEvent::DenialChallenge { challenger, claimant, card } => write!(f, "Player {} challenges Player {}'s claim not to have {}", challenger, claimant, card),
+            GameEvent::DenialResolved { claimant, card, success, .. } => if *success {
+                write!(f, "Player {} has {}", claimant, card)
+            } else {
+                write!(f, "Player {} does not have {}", claimant, card)
+            },
+            GameEvent::CardRevealed { player, card } => write!(f, "Player {} reveals {}", player, card),
+            GameEvent::CardReplaced { player, returned, drawn } => if *drawn == Card::None {
+                write!(f, "Player {} returns {} to the deck and draws a new card", player, returned)
+            } else {
+                write!(f, "Player {} returns {} to the deck and draws {}", player, returned, drawn)
+            },
+            GameEvent::CardShown { player, examiner, card } => if *card == Card::None {
+                write!(f, "Player {} shows Player {} a card", player, examiner)
+            } else {
+                write!(f, "Player {} shows Player {} {}", player, examiner, card)
+            },
+            GameEvent::CardSwapped { player, returned, drawn, .. } => if *returned == Card::None {
+                write!(f, "Player {} swaps the card they showed with the deck", player)
+            } else {
+                write!(f, "Player {} swaps {} with the deck and draws {}", player, returned, drawn)
+            },
+            GameEvent::Block { blocker, action, card, .. } => write!(f, "Player {} blocks {} with {}", blocker, action, card),
+            GameEvent::BlockChallenged { challenger, blocker, card } => write!(f, "Player {} challenges Player {}'s claim to have {}", challenger, blocker, card),
+            GameEvent::InfluenceLost { player, card } => write!(f, "Player {} loses {}", player, card),
+            GameEvent::CoinsMoved { from, to, amount } => match (from, to) {
+                (Some (i), Some (j)) => write!(f, "Player {} takes {} coins from Player {}", j, amount, i),
+                (Some (i), None) => write!(f, "Player {} pays {} coins", i, amount),
+                (None, Some (j)) => write!(f, "Player {} gains {} coins", j, amount),
+                (None, None) => Ok(()),
+            },
+            GameEvent::Exchange { player, drawn, .. } => write!(f, "Player {} exchanges {} cards with the deck", player, drawn.len()),
+            GameEvent::ReservePaid { player, amount } => write!(f, "Player {} pays {} coins into the Treasury Reserve", player, amount),
+            GameEvent::Embezzled { player, amount } => write!(f, "Player {} takes {} coins from the Treasury Reserve", player, amount),
+            GameEvent::Converted { player, target, faction } => if player == target {
+                write!(f, "Player {} converts to {}", player, faction)
+            } else {
+                write!(f, "Player {} converts Player {} to {}", player, target, faction)
+            },
+            GameEvent::ActionPerformed { player, action } => write!(f, "Player {} performs {}", player, action),
+            GameEvent::Eliminated { player } => write!(f, "Player {} is eliminated", player),
+            GameEvent::Win { player } => write!(f, "Player {} wins!", player),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Implements saving and loading agent genomes and training checkpoints.
+//!
+//! Both are JSON files that start with a format version, so that files
+//! written by older versions of this crate can be recognized.
+
+use std::{
+    fmt,
+    fs,
+    io,
+    path::Path,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+    de::DeserializeOwned,
+};
+
+use crate::PlayerMetadata;
+
+/// The version of the genome and checkpoint formats written by this crate.
+///
+/// Adding a genome field with a default does not change the version, since
+/// older files still load; any change that breaks older files does.
+const VERSION: u32 = 1;
+
+/// Enumerates the errors that can occur while saving or loading a genome
+/// or a checkpoint.
+#[derive(Debug)]
+pub enum GenomeError {
+    /// The file could not be read or written.
+    Io (io::Error),
+
+    /// The file is not valid JSON of the expected shape.
+    Json (serde_json::Error),
+
+    /// The file was written in a format version this crate cannot read.
+    Version (u32),
+}
+
+impl fmt::Display for GenomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenomeError::Io (e) => write!(f, "I/O error: {}", e),
+            GenomeError::Json (e) => write!(f, "invalid file: {}", e),
+            GenomeError::Version (version) => write!(f, "unsupported format version {}", version),
+        }
+    }
+}
+
+impl std::error::Error for GenomeError {}
+
+impl From<io::Error> for GenomeError {
+    fn from(e: io::Error) -> Self {
+        GenomeError::Io (e)
+    }
+}
+
+impl From<serde_json::Error> for GenomeError {
+    fn from(e: serde_json::Error) -> Self {
+        GenomeError::Json (e)
+    }
+}
+
+/// Holds the state of a training run, so that it can be resumed (see
+/// `Population::resume`).
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// The number of the generation the genomes belong to.
+    pub generation: usize,
+
+    /// The genomes of the population.
+    pub genomes: Vec<PlayerMetadata>,
+}
+
+/// Implements saving and loading checkpoints.
+impl Checkpoint {
+    /// Saves this checkpoint to the given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), GenomeError> {
+        save(path, self)
+    }
+
+    /// Loads a checkpoint from the given file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GenomeError> {
+        load(path)
+    }
+}
+
+/// Implements saving and loading genomes.
+impl PlayerMetadata {
+    /// Saves this genome to the given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), GenomeError> {
+        save(path, self)
+    }
+
+    /// Loads a genome from the given file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GenomeError> {
+        load(path)
+    }
+}
+
+/// Holds the contents of a file, tagged with the format version.
+#[derive(Serialize, Deserialize)]
+struct File<T> {
+    version: u32,
+    contents: T,
+}
+
+/// Holds just the format version of a file, which is read before the rest.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// Saves the given contents to a file in the current format.
+fn save<P: AsRef<Path>, T: Serialize>(path: P, contents: &T) -> Result<(), GenomeError> {
+    let file = File {
+        version: VERSION,
+        contents,
+    };
+
+    fs::write(path, serde_json::to_string_pretty(&file)?)?;
+    Ok(())
+}
+
+/// Loads the contents of a file, checking its format version first.
+fn load<P: AsRef<Path>, T: DeserializeOwned>(path: P) -> Result<T, GenomeError> {
+    let text = fs::read_to_string(path)?;
+
+    let header: Header = serde_json::from_str(&text)?;
+    if header.version != VERSION {
+        return Err(GenomeError::Version (header.version));
+    }
+
+    let file: File<T> = serde_json::from_str(&text)?;
+    Ok(file.contents)
+}
@@ -5,6 +5,7 @@ use crate::{
     Action,
     Player,
     PlayerMetadata,
+    GameView,
 };
 
 use inquire::{
@@ -12,9 +13,9 @@ use inquire::{
     MultiSelect,
 };
 
+#[derive(Clone, Debug)]
 pub struct Human {
     id: usize,
-    opponents: usize,
     hand: [Card; 2],
     coins: u8,
 }
@@ -45,32 +46,47 @@ impl Player for Human {
     }
 
     /// Exchanges cards (used on `Ambassador`).
-    fn exchange(&mut self, cards: &[Card]) -> Vec<Card> {
-        let mut options = self.hand.to_vec();
+    /// 
+    /// A player keeps one card for each influence they have left.
+    fn exchange(&mut self, _: &GameView, cards: &[Card]) -> Vec<Card> {
+        let mut options = self.hand.iter()
+            .copied()
+            .filter(|card| *card != Card::None)
+            .collect::<Vec<Card>>();
+        let influence = options.len();
         for card in cards {
             options.push(*card);
         }
 
-        let retained = match MultiSelect::new("Please select two cards to retain.", options.clone()).prompt() {
-            Ok(cards) => cards,
-            Err(_) => todo!(),
-        };
+        let prompt = format!("Please select {} card(s) to retain.", influence);
+        let retained = loop {
+            let retained = match MultiSelect::new(&prompt, options.clone()).prompt() {
+                Ok(cards) => cards,
+                Err(e) => {
+                    // Keep the cards this player already had
+                    println!("Could not read your choice ({}), so you keep your cards.", e);
+                    options[..influence].to_vec()
+                },
+            };
+
+            if retained.len() == influence {
+                break retained;
+            }
 
-        if retained.len() != 2 {
-            todo!();
-        }
+            println!("You must retain exactly {} card(s).", influence);
+        };
 
         // Note: it's OK to use `Option::unwrap` because we know that this element
         // exists in the list
+        for card in retained.iter() {
+            let index = options.iter().position(|x| x == card).unwrap();
+            options.remove(index);
+        }
 
-        let index1 = options.iter().position(|x| *x == retained[0]).unwrap();
-        options.remove(index1);
-
-        let index2 = options.iter().position(|x| *x == retained[1]).unwrap();
-        options.remove(index2);
-
-        self.hand[0] = retained[0];
-        self.hand[1] = retained[1];
+        self.hand = [Card::None, Card::None];
+        for (i, card) in retained.iter().enumerate() {
+            self.hand[i] = *card;
+        }
 
         options
     }
@@ -81,8 +97,24 @@ impl Player for Human {
     }
 
     /// Computes *a priori* probabilities of each player having certain cards.
-    fn compute_hands(&mut self, _: &[Card]) {
-        println!("You have {} and {}", self.hand[0], self.hand[1]);
+    fn compute_hands(&mut self, view: &GameView) {
+        if view.active_player == self.id {
+            println!("You have {} and {}", self.hand[0], self.hand[1]);
+
+            for i in view.opponents() {
+                println!("Player {} has {} coins and {} influence", i, view.players[i].coins, view.players[i].influence());
+            }
+
+            if view.rules.reformation {
+                for (i, player) in view.players.iter().enumerate() {
+                    if let Some (faction) = player.faction {
+                        println!("Player {} is a {}", i, faction);
+                    }
+                }
+
+                println!("The Treasury Reserve holds {} coins", view.reserve);
+            }
+        }
     }
 
     /// Deals the given cards to the player.
@@ -90,6 +122,19 @@ impl Player for Human {
         self.hand = hand;
     }
 
+    /// Asks this player which card to keep as the first card of their hand.
+    fn choose_influence(&mut self, _: &GameView, cards: &[Card]) -> Card {
+        match Select::new("Please select a card to keep.", cards.to_vec()).prompt() {
+            Ok(card) => card,
+            Err(e) => {
+                // Keep the first card rather than stop the game
+                let card = cards.first().copied().unwrap_or(Card::None);
+                println!("Could not read your choice ({}), so you keep {}.", e, card);
+                card
+            },
+        }
+    }
+
     /// Gains the number of coins specified.
     fn gain_coins(&mut self, coins: u8) {
         println!("You gain {} coins", coins);
@@ -113,7 +158,7 @@ impl Player for Human {
     }
 
     /// Forces the player to lose one influence.
-    fn lose_influence(&mut self) -> Card {
+    fn lose_influence(&mut self, _: &GameView) -> Card {
         let mut options = Vec::new();
 
         for card in self.hand {
@@ -122,7 +167,7 @@ impl Player for Human {
             }
         }
 
-        if options.len() == 0 {
+        if options.is_empty() {
             return Card::None;
         }
 
@@ -143,7 +188,7 @@ impl Player for Human {
     /// Asks this player if he challenges a claim.
     /// 
     /// Returns `true` if the player challenges and `false` otherwise.
-    fn check_challenge(&self, active_player: usize, card: Card) -> bool {
+    fn check_challenge(&mut self, view: &GameView, claimant: usize, card: Card) -> bool {
         if card == Card::None {
             // You can't challenge Income, ForeignAid, or Coup
             return false;
@@ -159,7 +204,11 @@ impl Player for Human {
             "No",
         ];
 
-        let prompt = format!("Do you challenge player {}'s claim to have {}?", active_player, card);
+        let prompt = match view.current {
+            Some (Action::Embezzle) if claimant == view.active_player => format!("Do you challenge player {}'s claim not to have {}?", claimant, card),
+            Some (action) if claimant == view.active_player => format!("Do you challenge player {}'s claim to have {} ({})?", claimant, card, action),
+            _ => format!("Do you challenge player {}'s claim to have {}?", claimant, card),
+        };
 
         let ans = match Select::new(&prompt, options).prompt() {
             Ok(a) => a,
@@ -177,16 +226,23 @@ impl Player for Human {
     /// 
     /// Returns `true` if the player blocks and `false` otherwise.  Also returns
     /// the card with which the player blocks.
-    fn check_block(&self, action: Action) -> (bool, Card) {
+    fn check_block(&mut self, view: &GameView, action: Action) -> (bool, Card) {
         if self.is_eliminated() {
             // You can't block if you're out
             return (false, Card::None);
         }
 
+        if view.allied(self.id, view.active_player) {
+            // You can't block a member of your own faction
+            return (false, Card::None);
+        }
+
+        // Anybody can block Foreign Aid, but only the target can block
+        // an Assassination or a Steal
         match action {
             Action::ForeignAid => (),
-            Action::Assassinate (_) => (),
-            Action::Steal (_) => (),
+            Action::Assassinate (target) if target == self.id => (),
+            Action::Steal (target) if target == self.id => (),
             _ => return (false, Card::None),
         }
 
@@ -195,7 +251,7 @@ impl Player for Human {
             "No",
         ];
 
-        let prompt = format!("Do you block {}?", action);
+        let prompt = format!("Do you block player {}'s {}?", view.active_player, action);
 
         let ans = match Select::new(&prompt, options).prompt() {
             Ok(a) => a,
@@ -205,20 +261,12 @@ impl Player for Human {
         match ans {
             "Yes" => if action == Action::ForeignAid {
                 (true, Card::Duke)
-            } else if let Action::Assassinate (target) = action {
-                if target == self.id {
-                    return (false, Card::None);
-                }
-
+            } else if let Action::Assassinate (_) = action {
                 (true, Card::Contessa)
-            } else if let Action::Steal (target) = action {
-                if target == self.id {
-                    return (false, Card::None);
-                }
-
+            } else if let Action::Steal (_) = action {
                 let options = vec![
                     Card::Captain,
-                    Card::Ambassador,
+                    view.rules.exchange_card(),
                 ];
 
                 let ans = match Select::new("With which card do you block?", options).prompt() {
@@ -236,101 +284,73 @@ impl Player for Human {
         }
     }
 
+    /// Asks this player which card to show to a player examining them.
+    fn show_card(&mut self, _: &GameView, examiner: usize) -> Card {
+        let options = self.hand.iter()
+            .copied()
+            .filter(|card| *card != Card::None)
+            .collect::<Vec<Card>>();
+
+        let prompt = format!("Player {} examines you.  Which card do you show?", examiner);
+
+        match Select::new(&prompt, options.clone()).prompt() {
+            Ok(card) => card,
+            Err(e) => {
+                // Show the first card rather than stop the game
+                let card = options.first().copied().unwrap_or(Card::None);
+                println!("Could not read your choice ({}), so you show {}.", e, card);
+                card
+            },
+        }
+    }
+
+    /// Asks this player whether or not to force an examined player to swap a card.
+    fn check_examine(&mut self, _: &GameView, target: usize, card: Card) -> bool {
+        let options = vec![
+            "Yes",
+            "No",
+        ];
+
+        let prompt = format!("Player {} shows you {}.  Do you force them to swap it with the deck?", target, card);
+
+        let ans = match Select::new(&prompt, options).prompt() {
+            Ok(a) => a,
+            Err(e) => {
+                println!("Could not read your choice ({}), so the card is not swapped.", e);
+                "No"
+            },
+        };
+
+        match ans {
+            "Yes" => true,
+            "No" => false,
+            _ => unreachable!(),
+        }
+    }
+
     /// Checks whether or not a player is still in the game.
     fn is_eliminated(&self) -> bool {
         self.hand == [Card::None, Card::None]
     }
 
     /// Select an action based on actions available.
-    fn select_action(&self, eliminated_players: &[usize]) -> Action {
-        let actions = self.get_available_actions(eliminated_players);
-
-        let mut options = Vec::new();
-
-        for action in actions {
-            options.push(action);
-        }
+    fn select_action(&mut self, view: &GameView) -> Action {
+        let options = view.legal_actions.clone();
 
-        let ans = match Select::new("Please select an action.", options).prompt() {
+        match Select::new("Please select an action.", options).prompt() {
             Ok(a) => a,
             Err(_) => todo!(),
-        };
-
-        ans
+        }
     }
 }
 
 impl Human {
-    /// Constructs a new player with the given ID and number of opponents.
-    pub fn new(id: usize, opponents: usize) -> Self {
+    /// Constructs a new player with the given ID.
+    pub fn new(id: usize) -> Self {
         Self {
             id,
-            opponents,
             hand: [Card::None, Card::None],
-            coins: 2,
-        }
-    }
-
-    /// Gets the actions available for this player.
-    fn get_available_actions(&self, eliminated_players: &[usize]) -> Vec<Action> {
-        // If this player is eliminated, he must Pass.
-        if self.is_eliminated() {
-            return vec![Action::Pass];
-        }
-
-        let mut actions: Vec<Action> = Vec::new();
-
-        // If this player has 10 coins or more, he must Coup.
-        if self.coins >= 10 {
-            for i in 0..=self.opponents {
-                if i != self.id && !eliminated_players.contains(&i) {
-                    actions.push(Action::Coup (i));
-                }
-            }
-
-            return actions;
-        }
-
-        // Income and ForeignAid are always available.
-        actions.push(Action::Income);
-        actions.push(Action::ForeignAid);
-
-        // Coup is available (for any player that is not this one) if this player has
-        // at least 7 coins.
-        if self.coins >= 7 {
-            for i in 0..=self.opponents {
-                if i != self.id && !eliminated_players.contains(&i) {
-                    actions.push(Action::Coup (i));
-                }
-            }
-        }
-
-        // For each card we have, we can take "safe" actions
-
-        // Dukes can Tax
-        actions.push(Action::Tax);
-
-        // Captains can Steal
-        for i in 0..=self.opponents {
-            if i != self.id && !eliminated_players.contains(&i) {
-                actions.push(Action::Steal (i));
-            }
+            coins: 0,
         }
-
-        // Ambassadors can Exchange
-        actions.push(Action::Exchange);
-
-        // Assassins can Assassinate
-        if self.coins >= 3 {
-            for i in 0..=self.opponents {
-                if i != self.id && !eliminated_players.contains(&i) {
-                    actions.push(Action::Assassinate (i));
-                }
-            }
-        }
-
-        // Contessas can't take any actions :(
-
-        actions
     }
 }
\ No newline at end of file
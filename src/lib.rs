@@ -4,25 +4,83 @@ mod engine;
 mod agent;
 mod player;
 mod human;
+mod event;
+mod record;
+mod view;
+mod rules;
+mod belief;
+mod distribution;
+mod search;
+mod cfr;
+mod training;
+mod batch;
+mod genome;
+mod tournament;
 
 use std::{
     fmt,
     collections::HashMap,
 };
 
-use rand::{
-    random,
+use rand::Rng;
+use serde::{
+    Deserialize,
+    Serialize,
 };
 
 pub use engine::{
     Engine,
+    EngineError,
 };
 
-pub use player::Player;
+pub use event::GameEvent;
+pub use record::{
+    GameRecord,
+    Decision,
+    RecordError,
+};
+pub use view::{
+    GameView,
+    PlayerState,
+};
+pub use rules::RuleSet;
+pub use belief::Belief;
+pub use distribution::Distribution;
+pub use search::{
+    Searcher,
+    Budget,
+};
+pub use cfr::{
+    CfrAgent,
+    CfrTrainer,
+    StrategyTable,
+    StrategyError,
+};
+pub use batch::Batch;
+pub use genome::{
+    Checkpoint,
+    GenomeError,
+};
+pub use training::{
+    Population,
+    TrainingConfig,
+    GenerationReport,
+};
+pub use tournament::{
+    Entrant,
+    Leaderboard,
+    Standing,
+    Tournament,
+    TournamentConfig,
+};
+pub use player::{
+    Player,
+    PlayerClone,
+};
 pub use agent::Agent;
 pub use human::Human;
 
-/// Enumerates the cards availaBy default, the items in a module have private visibility, but this can be overridden with the pub modifier. Only the public items of a module can be accessed from outside the module scope.ble in the game.
+/// Enumerates the cards available in the game.
 #[derive(Hash, PartialEq, Eq, Copy, Clone, Debug)]
 pub enum Card {
     Duke,
@@ -30,6 +88,9 @@ pub enum Card {
     Ambassador,
     Assassin,
     Contessa,
+
+    /// Replaces the Ambassador when playing with `RuleSet::inquisitor`.
+    Inquisitor,
     None,
 }
 
@@ -41,6 +102,7 @@ impl fmt::Display for Card {
             Card::Ambassador => "Ambassador",
             Card::Assassin => "Assassin",
             Card::Contessa => "Contessa",
+            Card::Inquisitor => "Inquisitor",
             Card::None => "None",
         };
 
@@ -49,7 +111,7 @@ impl fmt::Display for Card {
 }
 
 /// Enumerates the actions available in the game.
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[derive(Hash, PartialEq, Eq, Copy, Clone, Debug)]
 pub enum Action {
     Income,
 
@@ -70,6 +132,33 @@ pub enum Action {
 
     /// Note that a player can only `Pass` if he has been eliminated (has zero influence).
     Pass,
+
+    /// Changes the faction of the target, who may be the player taking the
+    /// action (Reformation only).  The cost is paid into the Treasury Reserve.
+    Convert (usize),
+
+    /// Takes every coin in the Treasury Reserve by claiming *not* to have a
+    /// Duke (Reformation only).
+    Embezzle,
+
+    /// Looks at one of the target's cards and may force them to swap it
+    /// with the deck (Inquisitor only).
+    Examine (usize),
+}
+
+/// Implements commonly used functions performed on actions.
+impl Action {
+    /// Gets the target of this action, if it has one.
+    pub fn target(&self) -> Option<usize> {
+        match *self {
+            Action::Coup (i)
+            | Action::Assassinate (i)
+            | Action::Steal (i)
+            | Action::Convert (i)
+            | Action::Examine (i) => Some (i),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Action {
@@ -83,6 +172,38 @@ impl fmt::Display for Action {
             Action::Exchange => "Exchange".to_string(),
             Action::Steal (i) => format!("Steal from player {}", i),
             Action::Pass => "Pass".to_string(),
+            Action::Convert (i) => format!("Convert player {}", i),
+            Action::Embezzle => "Embezzle".to_string(),
+            Action::Examine (i) => format!("Examine player {}", i),
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+/// Enumerates the factions of the Reformation expansion.
+#[derive(Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Faction {
+    Loyalist,
+    Reformist,
+}
+
+/// Implements commonly used functions performed on factions.
+impl Faction {
+    /// Gets the other faction.
+    pub fn other(&self) -> Self {
+        match self {
+            Faction::Loyalist => Faction::Reformist,
+            Faction::Reformist => Faction::Loyalist,
+        }
+    }
+}
+
+impl fmt::Display for Faction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = match self {
+            Faction::Loyalist => "Loyalist",
+            Faction::Reformist => "Reformist",
         };
 
         write!(f, "{}", output)
@@ -90,7 +211,7 @@ impl fmt::Display for Action {
 }
 
 /// Holds a distribution of utilities for each action.
-#[derive(Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct ActionUtilities {
     pub income: f64,
     pub foreignaid: f64,
@@ -99,52 +220,360 @@ pub struct ActionUtilities {
     pub assassinate: f64,
     pub exchange: f64,
     pub steal: f64,
+    pub convert: f64,
+    pub embezzle: f64,
+    pub examine: f64,
 }
 
 /// Implements commonly used functions performed on action utilities.
 impl ActionUtilities {
     /// Constructs a new, random action utility table.
-    pub fn random() -> Self {
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         Self {
-            income: 10.0*random::<f64>(),
-            foreignaid: 10.0*random::<f64>(),
-            coup: 10.0*random::<f64>(),
-            tax: 10.0*random::<f64>(),
-            assassinate: 10.0*random::<f64>(),
-            exchange: 10.0*random::<f64>(),
-            steal: 10.0*random::<f64>(),
+            income: 10.0*rng.gen::<f64>(),
+            foreignaid: 10.0*rng.gen::<f64>(),
+            coup: 10.0*rng.gen::<f64>(),
+            tax: 10.0*rng.gen::<f64>(),
+            assassinate: 10.0*rng.gen::<f64>(),
+            exchange: 10.0*rng.gen::<f64>(),
+            steal: 10.0*rng.gen::<f64>(),
+            convert: 10.0*rng.gen::<f64>(),
+            embezzle: 10.0*rng.gen::<f64>(),
+            examine: 10.0*rng.gen::<f64>(),
         }
     }
 
-    /// "Mutates" an action utilities table by a small amount.
-    pub fn mutate(&self) -> Self {
+    /// "Mutates" an action utilities table by a small amount in either
+    /// direction.
+    pub fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> Self {
         Self {
-            income: self.income + 0.1*random::<f64>(),
-            foreignaid: self.foreignaid + 0.1*random::<f64>(),
-            coup: self.coup + 0.1*random::<f64>(),
-            tax: self.tax + 0.1*random::<f64>(),
-            assassinate: self.assassinate + 0.1*random::<f64>(),
-            exchange: self.exchange + 0.1*random::<f64>(),
-            steal: self.steal + 0.1*random::<f64>(),
+            income: self.income + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            foreignaid: self.foreignaid + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            coup: self.coup + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            tax: self.tax + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            assassinate: self.assassinate + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            exchange: self.exchange + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            steal: self.steal + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            convert: self.convert + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            embezzle: self.embezzle + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            examine: self.examine + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+        }
+    }
+}
+
+/// Holds the weights an agent gives to the reasons for blocking an action,
+/// and for bluffing a block.
+///
+/// The default weights never block Foreign Aid on behalf of the table, take
+/// the table to challenge a block exactly as often as it is implausible, and
+/// give no credit for earlier claims.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct BlockWeights {
+    /// Weighs the worth of blocking Foreign Aid on behalf of the table, which
+    /// grows as the aid brings the actor closer to a Coup.
+    pub aid: f64,
+
+    /// Weighs how likely the table is to challenge a block it finds
+    /// implausible.
+    pub suspicion: f64,
+
+    /// Weighs how much more believable a block with a card is if the agent
+    /// has claimed that card before.
+    pub consistency: f64,
+}
+
+impl Default for BlockWeights {
+    fn default() -> Self {
+        Self {
+            aid: 0.0,
+            suspicion: 1.0,
+            consistency: 0.0,
+        }
+    }
+}
+
+/// Implements commonly used functions performed on block weights.
+impl BlockWeights {
+    /// Constructs a new, random set of weights.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self {
+            aid: rng.gen(),
+            suspicion: rng.gen(),
+            consistency: rng.gen(),
+        }
+    }
+
+    /// "Mutates" a set of weights by a small amount in either direction.
+    pub fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> Self {
+        Self {
+            aid: self.aid + 0.01*(2.0*rng.gen::<f64>() - 1.0),
+            suspicion: self.suspicion + 0.01*(2.0*rng.gen::<f64>() - 1.0),
+            consistency: self.consistency + 0.01*(2.0*rng.gen::<f64>() - 1.0),
+        }
+    }
+}
+
+/// Holds the weights a utility model gives to each feature of the game when
+/// scoring one kind of action.
+///
+/// Every feature lies between 0 and 1, and is seen from the point of view of
+/// the agent scoring the action.  The default weights are all 0.
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct FeatureWeights {
+    /// Weighs the agent's coins, as a share of the forced coup threshold.
+    pub coins: f64,
+
+    /// Weighs the agent's remaining influence, as a share of two.
+    pub influence: f64,
+
+    /// Weighs the coins of the target of the action, or of the richest
+    /// opponent if it has no target, as a share of the forced coup threshold.
+    pub opponent_coins: f64,
+
+    /// Weighs the remaining influence of the target of the action, or the
+    /// average of the opponents' if it has no target, as a share of two.
+    pub opponent_influence: f64,
+
+    /// Weighs whether the agent can take the action honestly (1) or would be
+    /// bluffing (0).
+    pub honest: f64,
+
+    /// Weighs the chance that the target of the action, or any opponent if
+    /// it has no target, holds a card that blocks it.
+    pub blocked: f64,
+}
+
+/// Implements commonly used functions performed on feature weights.
+impl FeatureWeights {
+    /// Constructs a new, random set of weights, each between -1 and 1.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self {
+            coins: 2.0*rng.gen::<f64>() - 1.0,
+            influence: 2.0*rng.gen::<f64>() - 1.0,
+            opponent_coins: 2.0*rng.gen::<f64>() - 1.0,
+            opponent_influence: 2.0*rng.gen::<f64>() - 1.0,
+            honest: 2.0*rng.gen::<f64>() - 1.0,
+            blocked: 2.0*rng.gen::<f64>() - 1.0,
+        }
+    }
+
+    /// "Mutates" a set of weights by a small amount in either direction.
+    pub fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> Self {
+        Self {
+            coins: self.coins + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            influence: self.influence + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            opponent_coins: self.opponent_coins + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            opponent_influence: self.opponent_influence + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            honest: self.honest + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+            blocked: self.blocked + 0.1*(2.0*rng.gen::<f64>() - 1.0),
+        }
+    }
+}
+
+/// Holds a linear model of how the utility of each action changes with the
+/// state of the game.
+///
+/// An agent adds the model's score for an action to the action's utility in
+/// its `ActionUtilities` table, so the table gives each action a constant
+/// worth and the model adjusts it to the situation.  The default model adds
+/// nothing.
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct UtilityModel {
+    pub income: FeatureWeights,
+    pub foreignaid: FeatureWeights,
+    pub coup: FeatureWeights,
+    pub tax: FeatureWeights,
+    pub assassinate: FeatureWeights,
+    pub exchange: FeatureWeights,
+    pub steal: FeatureWeights,
+    pub convert: FeatureWeights,
+    pub embezzle: FeatureWeights,
+    pub examine: FeatureWeights,
+}
+
+/// Implements commonly used functions performed on utility models.
+impl UtilityModel {
+    /// Constructs a new, random utility model.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self {
+            income: FeatureWeights::random(rng),
+            foreignaid: FeatureWeights::random(rng),
+            coup: FeatureWeights::random(rng),
+            tax: FeatureWeights::random(rng),
+            assassinate: FeatureWeights::random(rng),
+            exchange: FeatureWeights::random(rng),
+            steal: FeatureWeights::random(rng),
+            convert: FeatureWeights::random(rng),
+            embezzle: FeatureWeights::random(rng),
+            examine: FeatureWeights::random(rng),
+        }
+    }
+
+    /// "Mutates" a utility model by a small amount in either direction.
+    pub fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> Self {
+        Self {
+            income: self.income.mutate(rng),
+            foreignaid: self.foreignaid.mutate(rng),
+            coup: self.coup.mutate(rng),
+            tax: self.tax.mutate(rng),
+            assassinate: self.assassinate.mutate(rng),
+            exchange: self.exchange.mutate(rng),
+            steal: self.steal.mutate(rng),
+            convert: self.convert.mutate(rng),
+            embezzle: self.embezzle.mutate(rng),
+            examine: self.examine.mutate(rng),
+        }
+    }
+
+    /// Gets the weights for the given action, or `None` for `Action::Pass`.
+    pub fn get(&self, action: Action) -> Option<&FeatureWeights> {
+        match action {
+            Action::Income => Some (&self.income),
+            Action::ForeignAid => Some (&self.foreignaid),
+            Action::Coup (_) => Some (&self.coup),
+            Action::Tax => Some (&self.tax),
+            Action::Assassinate (_) => Some (&self.assassinate),
+            Action::Exchange => Some (&self.exchange),
+            Action::Steal (_) => Some (&self.steal),
+            Action::Pass => None,
+            Action::Convert (_) => Some (&self.convert),
+            Action::Embezzle => Some (&self.embezzle),
+            Action::Examine (_) => Some (&self.examine),
+        }
+    }
+}
+
+/// Holds the weights an agent gives to each reason for keeping a card when
+/// it has to lose an influence.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct InfluenceWeights {
+    /// Weighs the utility of the action the card lets the agent take.
+    pub action: f64,
+
+    /// Weighs the utility of the attacks on the agent the card blocks.
+    pub defense: f64,
+
+    /// Weighs the utility of the card's action again if the agent has
+    /// claimed the card, since losing a claimed card discredits the claim.
+    pub claim: f64,
+}
+
+/// Implements commonly used functions performed on influence weights.
+impl InfluenceWeights {
+    /// Constructs a new, random set of weights.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self {
+            action: rng.gen(),
+            defense: rng.gen(),
+            claim: rng.gen(),
+        }
+    }
+
+    /// "Mutates" a set of weights by a small amount in either direction.
+    pub fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> Self {
+        Self {
+            action: self.action + 0.01*(2.0*rng.gen::<f64>() - 1.0),
+            defense: self.defense + 0.01*(2.0*rng.gen::<f64>() - 1.0),
+            claim: self.claim + 0.01*(2.0*rng.gen::<f64>() - 1.0),
+        }
+    }
+}
+
+/// Holds the weights an agent gives to each feature of an opponent when
+/// choosing whom to Coup, Assassinate, or Steal from.
+///
+/// Every feature lies between 0 and 1, and a negative weight steers the
+/// agent away from opponents with more of it.  The default weights are all
+/// 0, so that no opponent is preferred over another.
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct TargetWeights {
+    /// Weighs the opponent's coins, as a share of the forced coup threshold.
+    pub coins: f64,
+
+    /// Weighs the opponent's remaining influence, as a share of two.
+    pub influence: f64,
+
+    /// Weighs the chance that the opponent can attack the agent on its next
+    /// turn.
+    pub threat: f64,
+
+    /// Weighs the chance that the opponent holds a card that blocks the
+    /// action.
+    pub blockers: f64,
+}
+
+/// Implements commonly used functions performed on target weights.
+impl TargetWeights {
+    /// Constructs a new, random set of weights, each between -1 and 1.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self {
+            coins: 2.0*rng.gen::<f64>() - 1.0,
+            influence: 2.0*rng.gen::<f64>() - 1.0,
+            threat: 2.0*rng.gen::<f64>() - 1.0,
+            blockers: 2.0*rng.gen::<f64>() - 1.0,
+        }
+    }
+
+    /// "Mutates" a set of weights by a small amount in either direction.
+    pub fn mutate<R: Rng + ?Sized>(&self, rng: &mut R) -> Self {
+        Self {
+            coins: self.coins + 0.01*(2.0*rng.gen::<f64>() - 1.0),
+            influence: self.influence + 0.01*(2.0*rng.gen::<f64>() - 1.0),
+            threat: self.threat + 0.01*(2.0*rng.gen::<f64>() - 1.0),
+            blockers: self.blockers + 0.01*(2.0*rng.gen::<f64>() - 1.0),
         }
     }
 }
 
 /// Holds player metadata.
-#[derive(Clone, Copy, Debug)]
+// Genomes are copied around freely during training, so the large
+// `Computer` variant is kept inline rather than boxed
+#[allow(clippy::large_enum_variant)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum PlayerMetadata {
     Human,
+    Searcher,
+
+    /// A `CfrAgent` playing a trained strategy table.
+    Cfr,
     Computer {
         lying_cutoff: f64,
         liar_cutoff: f64,
         utilities: ActionUtilities,
+        influence: InfluenceWeights,
+
+        // Genomes saved before an agent had the fields below still load,
+        // with the neutral defaults in their place
+        #[serde(default)]
+        targeting: TargetWeights,
+        #[serde(default)]
+        blocking: BlockWeights,
+
+        /// Adjusts the utilities to the state of the game.
+        #[serde(default)]
+        model: UtilityModel,
+
+        /// Softens this agent's decisions: at 0, it always plays its most
+        /// useful action and compares its beliefs to its cutoffs exactly;
+        /// the higher it is, the more often it plays a less useful action
+        /// or challenges and blocks against its cutoffs.
+        #[serde(default)]
+        temperature: f64,
     },
 }
 
-/// Holds a perceived hand.
+/// Holds a perceived hand: the probability of a player holding at least one
+/// copy, and of holding a pair, of each card.
 #[derive(Clone, Debug)]
 pub struct PerceivedHand {
     hand: HashMap<Card, f64>,
+    pairs: HashMap<Card, f64>,
+}
+
+impl Default for PerceivedHand {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Implements commonly used functions performed on perceived hands.
@@ -157,32 +586,62 @@ impl PerceivedHand {
             (Card::Ambassador, 0.0),
             (Card::Assassin, 0.0),
             (Card::Contessa, 0.0),
+            (Card::Inquisitor, 0.0),
         ]);
 
         Self {
+            pairs: hand.clone(),
             hand,
         }
     }
 
-    /// Constructs a new perceived hand from a given hashmap.
+    /// Constructs a new perceived hand from a given hashmap of the
+    /// probabilities of holding at least one copy of each card.
     pub fn from(hand: HashMap<Card, f64>) -> Self {
         Self {
             hand,
+            pairs: HashMap::new(),
         }
     }
 
-    /// Gets a probability from the hand.
+    /// Constructs a new perceived hand from a list of possible hands and
+    /// their probabilities (see `Distribution::hands` and `Belief::hands`).
+    pub fn from_hands(cards: &[Card], hands: &[(Vec<Card>, f64)]) -> Self {
+        let mut perceived = Self {
+            hand: HashMap::new(),
+            pairs: HashMap::new(),
+        };
+
+        for &card in cards {
+            let count = |hand: &Vec<Card>| hand.iter().filter(|c| **c == card).count();
+
+            perceived.hand.insert(card, hands.iter().filter(|(hand, _)| count(hand) >= 1).map(|(_, p)| p).sum());
+            perceived.pairs.insert(card, hands.iter().filter(|(hand, _)| count(hand) >= 2).map(|(_, p)| p).sum());
+        }
+
+        perceived
+    }
+
+    /// Gets the probability of holding at least one copy of a card.
     pub fn get(&self, card: &Card) -> Option<&f64> {
         self.hand.get(card)
     }
 
+    /// Gets the probability of holding two copies of a card.
+    pub fn get_pair(&self, card: &Card) -> Option<&f64> {
+        self.pairs.get(card)
+    }
+
     /// Assigns a given hand (if it is known with certainty).
-    pub fn assign(&mut self, cards: [Card; 2]) {
-        if cards[0] != cards[1] {
-            self.hand.insert(cards[0], 1.0);
-            self.hand.insert(cards[1], 1.0);
-        } else {
-            self.hand.insert(cards[0], 2.0);
+    /// 
+    /// `cards` lists the player's remaining influence; lost cards may be
+    /// given as `Card::None`.
+    pub fn assign(&mut self, cards: &[Card]) {
+        for (card, probability) in self.hand.iter_mut() {
+            let count = cards.iter().filter(|c| *c == card).count();
+
+            *probability = if count >= 1 { 1.0 } else { 0.0 };
+            self.pairs.insert(*card, if count >= 2 { 1.0 } else { 0.0 });
         }
     }
-}
\ No newline at end of file
+}
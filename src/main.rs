@@ -1,116 +1,365 @@
 //! Main executable for the Contessa Coup Engine.
 
+use std::{
+    env,
+    io,
+    path::Path,
+    sync::Arc,
+};
+
 use contessa::{
     Agent,
     Human,
     Player,
     PlayerMetadata,
     ActionUtilities,
+    InfluenceWeights,
+    TargetWeights,
+    BlockWeights,
+    UtilityModel,
+    CfrTrainer,
+    Checkpoint,
     Engine,
+    Entrant,
+    GameRecord,
+    Population,
+    RuleSet,
+    StrategyTable,
+    Tournament,
+    TournamentConfig,
+    TrainingConfig,
 };
 
+const USAGE: &str = "Usage:
+    cts [play] [--record <file>] [--agent <file>]...
+                                    Play one human against three computers,
+                                    the first of them loaded from the given
+                                    genome files
+    cts replay <file>               Step through a recorded game
+    cts train <generations> <dir>   Evolve a population of computer players,
+                                    saving checkpoints to the directory
+    cts cfr <iterations> <file>     Train a strategy table for four players
+    cts tournament <rounds> <file> <file>...
+                                    Rate genomes and strategy tables against
+                                    each other in four-player games";
+
 fn main() {
-    play_alone();
+    let args = env::args().skip(1).collect::<Vec<String>>();
+
+    match args.iter().map(|a| a.as_str()).collect::<Vec<&str>>()[..] {
+        ["replay", file] => replay(file),
+        ["train", generations, dir] => match generations.parse::<usize>() {
+            Ok(generations) => train(generations, dir),
+            Err(_) => println!("{}", USAGE),
+        },
+        ["cfr", iterations, file] => match iterations.parse::<usize>() {
+            Ok(iterations) => train_cfr(iterations, file),
+            Err(_) => println!("{}", USAGE),
+        },
+        ["tournament", rounds, ref files @ ..] if files.len() >= 2 => match rounds.parse::<usize>() {
+            Ok(rounds) => tournament(rounds, files),
+            Err(_) => println!("{}", USAGE),
+        },
+        ["play", ref options @ ..] | ref options => match play_options(options) {
+            Some ((record, agents)) => play_alone(record, &agents),
+            None => println!("{}", USAGE),
+        },
+    }
+}
+
+/// Parses the options of `cts play` into the file to record the game to,
+/// if any, and the genome files of the opponents.
+/// 
+/// Gets `None` if an option is unknown or is missing its file.
+fn play_options<'a>(options: &[&'a str]) -> Option<(Option<&'a str>, Vec<&'a str>)> {
+    let mut record = None;
+    let mut agents = Vec::new();
+
+    let mut options = options.iter();
+    while let Some (option) = options.next() {
+        match *option {
+            "--record" => record = Some (*options.next()?),
+            "--agent" => agents.push(*options.next()?),
+            _ => return None,
+        }
+    }
+
+    Some ((record, agents))
+}
+
+/// Builds one of the default computer opponents from its evolved cutoffs and
+/// the utilities of the seven base actions (Income through Steal).  These
+/// were evolved before agents had target or block weights, so the rest of
+/// the genome is the same for every default opponent, with the default
+/// weights.
+fn opponent(id: usize, lying_cutoff: f64, liar_cutoff: f64, base: [f64; 7]) -> Box<dyn Player> {
+    let [income, foreignaid, coup, tax, assassinate, exchange, steal] = base;
+
+    Box::new(Agent::from_metadata(id, 3, PlayerMetadata::Computer {
+        lying_cutoff,
+        liar_cutoff,
+        utilities: ActionUtilities {
+            income,
+            foreignaid,
+            coup,
+            tax,
+            assassinate,
+            exchange,
+            steal,
+            convert: 0.0,
+            embezzle: 0.0,
+            examine: 0.0,
+        },
+        influence: InfluenceWeights {
+            action: 1.0,
+            defense: 1.0,
+            claim: 1.0,
+        },
+        targeting: TargetWeights::default(),
+        blocking: BlockWeights::default(),
+        model: UtilityModel::default(),
+        temperature: 0.0,
+    }))
 }
 
 /// Play one human against three computers.
-#[allow(dead_code)]
-fn play_alone() {
-    let players: Vec<Box<dyn Player>> = vec![
-        Box::new(Human::new(0, 3)),
-        Box::new(Agent::from_metadata(1, 3, PlayerMetadata::Computer {
-            lying_cutoff: 0.466364326387811,
-            liar_cutoff: 0.33289578312910617,
-            utilities: ActionUtilities {
-                income: 195.36372639502153,
-                foreignaid: 199.18521202140687,
-                coup: 198.5313333717328,
-                tax: 198.7999675135545,
-                assassinate: 196.14223304005097,
-                exchange: 201.80819297274235,
-                steal: 202.13162395101668,
+/// 
+/// If a file is given, the game record is saved to it when the game ends.
+/// Genomes loaded from the `agents` files replace the default computers,
+/// in seat order.
+fn play_alone(record: Option<&str>, agents: &[&str]) {
+    if agents.len() > 3 {
+        println!("At most three agents can be loaded");
+        return;
+    }
+
+    let mut players: Vec<Box<dyn Player>> = vec![
+        Box::new(Human::new(0)),
+        opponent(1, 0.466364326387811, 0.33289578312910617, [
+            195.36372639502153,
+            199.18521202140687,
+            198.5313333717328,
+            198.7999675135545,
+            196.14223304005097,
+            201.80819297274235,
+            202.13162395101668,
+        ]),
+        opponent(2, 0.3183643574140307, 0.13772319681034595, [
+            90.70180802270514,
+            91.59549131861426,
+            98.66347002003363,
+            90.90620660166468,
+            91.05944769508035,
+            89.00886263065826,
+            90.85852199512367,
+        ]),
+        opponent(3, 0.41882864006755455, 0.5430394967947864, [
+            225.71723906401857,
+            227.8320028848388,
+            230.58294609447898,
+            235.9721313644429,
+            229.04454321216778,
+            229.58909242010145,
+            237.98150857824476,
+        ]),
+    ];
+
+    for (seat, file) in agents.iter().enumerate() {
+        let id = seat + 1;
+        match PlayerMetadata::load(file) {
+            Ok(metadata @ PlayerMetadata::Computer { .. }) => {
+                players[id] = Box::new(Agent::from_metadata(id, 3, metadata));
             },
-        })),
-        Box::new(Agent::from_metadata(2, 3, PlayerMetadata::Computer {
-            lying_cutoff: 0.3183643574140307,
-            liar_cutoff: 0.13772319681034595,
-            utilities: ActionUtilities {
-                income: 90.70180802270514,
-                foreignaid: 91.59549131861426,
-                coup: 98.66347002003363,
-                tax: 90.90620660166468,
-                assassinate: 91.05944769508035,
-                exchange: 89.00886263065826,
-                steal: 90.85852199512367,
+            Ok(_) => {
+                println!("{} is not a computer genome", file);
+                return;
             },
-        })),
-        Box::new(Agent::from_metadata(3, 3, PlayerMetadata::Computer {
-            lying_cutoff: 0.41882864006755455,
-            liar_cutoff: 0.5430394967947864,
-            utilities: ActionUtilities {
-                income: 225.71723906401857,
-                foreignaid: 227.8320028848388,
-                coup: 230.58294609447898,
-                tax: 235.9721313644429,
-                assassinate: 229.04454321216778,
-                exchange: 229.58909242010145,
-                steal: 237.98150857824476,
+            Err(e) => {
+                println!("Could not load {}: {}", file, e);
+                return;
             },
-        })),
-    ];
+        }
+    }
 
-    let mut engine = Engine::new(players);
-    let _ = engine.play(true);
+    let rules = RuleSet::for_players(players.len());
+    let mut engine = match Engine::new(players, rules) {
+        Ok(engine) => engine,
+        Err(e) => {
+            println!("Could not start the game: {}", e);
+            return;
+        },
+    };
+    if let Err(e) = engine.play(true) {
+        println!("The game was stopped: {}", e);
+    }
+
+    if let Some (file) = record {
+        match engine.get_record().save(file) {
+            Ok(_) => println!("Game saved to {}", file),
+            Err(e) => println!("Could not save game: {}", e),
+        }
+    }
 
     println!("Thanks for playing!");
 }
 
-/// Train 1000 Contessa Engines against one another for a specified number of generations.
-#[allow(dead_code)]
-pub fn train(generations: usize) {
-    // FIRST GENERATION //
-    let mut winners = Vec::new();
-
-    for i in 0..1000 {
-        let players: Vec<Box<dyn Player>> = vec![
-            Box::new(Agent::new(0, 3)),
-            Box::new(Agent::new(1, 3)),
-            Box::new(Agent::new(2, 3)),
-            Box::new(Agent::new(3, 3)),
-        ];
-        let mut engine = Engine::new(players);
-        let player = engine.play(false);
-
-        // Reset this player
-        winners.push(player);
-
-        println!("Generation 0, Game {} complete", i);
+/// Step through a recorded game one turn at a time.
+fn replay(file: &str) {
+    let record = match GameRecord::load(file) {
+        Ok(record) => record,
+        Err(e) => {
+            println!("Could not load {}: {}", file, e);
+            return;
+        },
+    };
+
+    let mut engine = match record.engine_at(0) {
+        Ok(engine) => engine,
+        Err(e) => {
+            println!("Could not replay {}: {}", file, e);
+            return;
+        },
+    };
+
+    for event in engine.get_events() {
+        println!("{}", event);
+    }
+
+    for turn in 1..=record.turns.len() {
+        println!();
+        println!("Press Enter to play turn {} of {}", turn, record.turns.len());
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line).is_err() {
+            return;
+        }
+
+        let seen = engine.get_events().len();
+        if let Err(e) = engine.turn(false) {
+            println!("Could not replay turn {}: {}", turn, e);
+            return;
+        }
+
+        for event in &engine.get_events()[seen..] {
+            println!("{}", event);
+        }
+    }
+}
+
+/// Train a strategy table for four players with MCCFR and save it.
+/// 
+/// Progress and an estimate of the exploitability of the strategy are
+/// printed ten times over the course of training.
+fn train_cfr(iterations: usize, file: &str) {
+    let mut trainer = CfrTrainer::new(RuleSet::default(), 4);
+
+    for round in 1..=10 {
+        if let Err(e) = trainer.train(iterations/10) {
+            println!("Training stopped: {}", e);
+            return;
+        }
+
+        match trainer.exploitability(1000, 500) {
+            Ok(exploitability) => println!("{}/10: {} games, exploitability about {:.3}", round, trainer.get_iterations(), exploitability),
+            Err(e) => println!("{}/10: could not estimate exploitability: {}", round, e),
+        }
     }
 
-    // FOLLOWING GENERATIONS //
+    match trainer.strategy().save(file) {
+        Ok(_) => println!("Strategy saved to {}", file),
+        Err(e) => println!("Could not save strategy: {}", e),
+    }
+}
+
+/// Train a population of agents for a specified number of generations,
+/// reporting on each generation as it finishes.
+/// 
+/// After each generation, the population is saved to `checkpoint.json` and
+/// its champion to `best.json` in the given directory.  If a checkpoint is
+/// already there, training resumes from it.
+fn train(generations: usize, dir: &str) {
+    let dir = Path::new(dir);
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        println!("Could not create {}: {}", dir.display(), e);
+        return;
+    }
 
-    for gen in 1..generations {
-        // Mutate players and set up new games
-        let mut new_winners = Vec::new();
+    let checkpoint = dir.join("checkpoint.json");
+    let best = dir.join("best.json");
 
-        for (i, &player_metadata) in winners.iter().enumerate() {
-            let player = Agent::from_metadata(0, 3, player_metadata);
-            let players: Vec<Box<dyn Player>> = vec![
-                Box::new(player.clone()),
-                Box::new(player.mutate().with_id(1)),
-                Box::new(player.mutate().with_id(2)),
-                Box::new(player.mutate().with_id(3)),
-            ];
-            let mut engine = Engine::new(players);
-            let player = engine.play(false);
+    let mut population = if checkpoint.exists() {
+        match Checkpoint::load(&checkpoint) {
+            Ok(saved) => {
+                println!("Resuming from generation {}", saved.generation);
+                Population::resume(TrainingConfig::default(), saved, rand::random())
+            },
+            Err(e) => {
+                println!("Could not load {}: {}", checkpoint.display(), e);
+                return;
+            },
+        }
+    } else {
+        Population::new(TrainingConfig::default())
+    };
 
-            new_winners.push(player);
+    for _ in 0..generations {
+        let report = match population.step() {
+            Ok(report) => report,
+            Err(e) => {
+                println!("Training stopped: {}", e);
+                return;
+            },
+        };
+        println!("{}", report);
 
-            println!("Generation {}, Game {} complete", gen, i);
+        if let Err(e) = population.checkpoint().save(&checkpoint) {
+            println!("Could not save {}: {}", checkpoint.display(), e);
+            return;
         }
+        if let Err(e) = report.champion.save(&best) {
+            println!("Could not save {}: {}", best.display(), e);
+            return;
+        }
+    }
+
+    println!("Best genome saved to {}", best.display());
+}
 
-        winners = new_winners;
+/// Play a round-robin tournament between the genomes and strategy tables
+/// in the given files, and print the leaderboard.
+/// 
+/// Each entrant is named after its file.
+fn tournament(rounds: usize, files: &[&str]) {
+    let mut tournament = Tournament::new(TournamentConfig {
+        rounds,
+        ..TournamentConfig::default()
+    });
+
+    for file in files {
+        let name = Path::new(file)
+            .file_stem()
+            .map_or(file.to_string(), |stem| stem.to_string_lossy().to_string());
+
+        let entrant = match PlayerMetadata::load(file) {
+            Ok(metadata @ PlayerMetadata::Computer { .. }) => Entrant::Genome (metadata),
+            Ok(_) => {
+                println!("{} is not a computer genome", file);
+                return;
+            },
+            Err(genome) => match StrategyTable::load(file) {
+                Ok(table) => Entrant::Strategy (Arc::new(table)),
+                Err(strategy) => {
+                    println!("Could not load {} as a genome ({}) or a strategy table ({})", file, genome, strategy);
+                    return;
+                },
+            },
+        };
+
+        tournament.enter(&name, entrant);
     }
 
-    dbg!(&winners);
-}
\ No newline at end of file
+    println!("Playing {} games...", tournament.schedule().len());
+    match tournament.play() {
+        Ok(leaderboard) => print!("{}", leaderboard),
+        Err(e) => println!("The tournament was stopped: {}", e),
+    }
+}
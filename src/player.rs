@@ -3,10 +3,38 @@
 use crate::{
     Card,
     Action,
-    PlayerMetadata
+    PlayerMetadata,
+    GameView,
 };
 
-pub trait Player {
+/// Allows a boxed player to be cloned, so that a whole `Engine` can be.
+/// 
+/// This is implemented for every `Player` that implements `Clone`.
+pub trait PlayerClone {
+    /// Clones this player into a new box.
+    fn clone_box(&self) -> Box<dyn Player>;
+}
+
+impl<T: 'static + Player + Clone> PlayerClone for T {
+    fn clone_box(&self) -> Box<dyn Player> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Player> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Defines the decisions a player makes over the course of a game.
+/// 
+/// Every decision receives a `GameView` built by the engine, which holds
+/// all of the public information about the game plus this player's hand.
+/// 
+/// Players must be `Send`, so that engines can be moved to other threads
+/// (see `Batch`).
+pub trait Player: PlayerClone + Send {
     /// Gets the metadata of the given player.
     fn get_metadata(&self) -> PlayerMetadata;
 
@@ -20,17 +48,29 @@ pub trait Player {
     fn replace(&mut self, current: Card, new: Card);
 
     /// Exchanges cards (used on `Ambassador`).
-    fn exchange(&mut self, cards: &[Card]) -> Vec<Card>;
+    /// 
+    /// Returns the cards to put back into the deck, which must be as many as
+    /// were drawn.
+    fn exchange(&mut self, view: &GameView, cards: &[Card]) -> Vec<Card>;
 
     /// Gets the number of coins this player has.
     fn get_coins(&self) -> u8;
 
     /// Computes *a priori* probabilities of each player having certain cards.
-    fn compute_hands(&mut self, killed: &[Card]);
+    /// 
+    /// This is called for every player at the start of every turn.
+    fn compute_hands(&mut self, view: &GameView);
 
     /// Deals the given cards to the player.
     fn deal(&mut self, hand: [Card; 2]);
 
+    /// Asks this player which of the given cards to keep as the first card
+    /// of their hand (used by `RuleSet::head_to_head`).
+    /// 
+    /// The second card is dealt from the deck afterwards, and both cards
+    /// are then given to this player with `Player::deal`.
+    fn choose_influence(&mut self, view: &GameView, cards: &[Card]) -> Card;
+
     /// Gains the number of coins specified.
     fn gain_coins(&mut self, coins: u8);
 
@@ -38,22 +78,43 @@ pub trait Player {
     fn lose_coins(&mut self, coins: u8) -> u8;
 
     /// Forces the player to lose one influence.
-    fn lose_influence(&mut self) -> Card;
+    fn lose_influence(&mut self, view: &GameView) -> Card;
 
     /// Asks this player if he challenges a claim.
     /// 
     /// Returns `true` if the player challenges and `false` otherwise.
-    fn check_challenge(&self, active_player: usize, card: Card) -> bool;
+    /// 
+    /// `claimant` is the player claiming to have `card`; the action being
+    /// resolved is `view.current`.  If that action is `Action::Embezzle`,
+    /// the claim is instead that `claimant` does *not* have `card`.
+    fn check_challenge(&mut self, view: &GameView, claimant: usize, card: Card) -> bool;
 
     /// Asks this player if he blocks an action.
     /// 
     /// Returns `true` if the player blocks and `false` otherwise.  Also returns
     /// the card with which the player blocks.
-    fn check_block(&self, action: Action) -> (bool, Card);
+    /// 
+    /// The player taking the action is `view.active_player`.
+    fn check_block(&mut self, view: &GameView, action: Action) -> (bool, Card);
+
+    /// Asks this player which card to show to a player examining them with
+    /// the Inquisitor.
+    /// 
+    /// The card must be one that this player still has.
+    fn show_card(&mut self, view: &GameView, examiner: usize) -> Card;
+
+    /// Asks this player, after examining `target`, whether or not to force
+    /// them to swap the card they showed (`card`) with the deck.
+    fn check_examine(&mut self, view: &GameView, target: usize, card: Card) -> bool;
 
     /// Checks whether or not a player is still in the game.
     fn is_eliminated(&self) -> bool;
 
     /// Select an action based on actions available.
-    fn select_action(&self, eliminated_players: &[usize]) -> Action;
+    fn select_action(&mut self, view: &GameView) -> Action;
+
+    /// Reseeds this player's random number generator.
+    /// 
+    /// Players that make no random decisions may ignore this.
+    fn reseed(&mut self, _seed: u64) {}
 }
\ No newline at end of file
@@ -0,0 +1,733 @@
+//! Implements a text format for saving, loading, and replaying games.
+//!
+//! A game record holds everything needed to reproduce a game: the seed,
+//! the rules, the initial deal, and every decision each player made.
+//! Records are stored as plain text.  Blank lines and anything following a
+//! `#` are ignored; every other line is a keyword followed by its
+//! arguments, separated by whitespace:
+//!
+//! ```text
+//! contessa 1                  # format version
+//! seed 1234                   # seed passed to `Engine::with_deck`
+//! rule coup_cost 6            # a rule that differs from the default
+//! deck Duke Captain ...       # initial order of the deck, top first
+//! players 4                   # number of players
+//! deal 0 Duke Captain         # initial hand of each player
+//! choose 0 Duke               # player 0 keeps a Duke (head to head only)
+//! turn                        # starts a new turn
+//! action 0 Steal(2)           # player 0 declares an action
+//! challenge 1 no              # player 1 declines to challenge
+//! block 2 Captain             # player 2 blocks with a Captain (`none` to pass)
+//! lose 0 Duke                 # player 0 chooses to lose a Duke
+//! exchange 3 Duke Contessa    # player 3 returns two cards to the deck
+//! show 1 Assassin             # player 1 shows an Assassin to an examiner
+//! examine 0 yes               # player 0 forces the card to be swapped
+//! ```
+//!
+//! Only the rules that differ from `RuleSet::default()` are written, by
+//! the names listed in `RuleSet::values`.  The `deck` line may be omitted,
+//! in which case the deck is shuffled from the seed as in
+//! `Engine::with_seed`.  Decisions are listed in the order in which the
+//! engine asked for them; decisions made while dealing come before the
+//! first `turn`.  Actions are written as `Income`, `ForeignAid`, `Coup(i)`,
+//! `Tax`, `Assassinate(i)`, `Exchange`, `Steal(i)`, `Pass`, `Convert(i)`,
+//! `Embezzle`, or `Examine(i)`.
+
+use std::{
+    fmt,
+    fs,
+    io,
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{
+    Card,
+    Action,
+    Player,
+    PlayerMetadata,
+    Engine,
+    EngineError,
+    GameView,
+    RuleSet,
+};
+
+/// The version of the record format written by this library.
+const VERSION: u32 = 1;
+
+/// Enumerates the decisions a player can be asked to make.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Decision {
+    /// A player declares an action.
+    Action {
+        player: usize,
+        action: Action,
+    },
+
+    /// A player decides whether or not to challenge a claim.
+    Challenge {
+        player: usize,
+        challenged: bool,
+    },
+
+    /// A player decides whether or not to block an action, and with which card.
+    Block {
+        player: usize,
+        card: Option<Card>,
+    },
+
+    /// A player chooses which card to lose.
+    LoseInfluence {
+        player: usize,
+        card: Card,
+    },
+
+    /// A player chooses which cards to return to the deck after an exchange.
+    Exchange {
+        player: usize,
+        returned: Vec<Card>,
+    },
+
+    /// A player chooses which card to show to the player examining them.
+    Show {
+        player: usize,
+        card: Card,
+    },
+
+    /// A player decides whether or not to force the player they examined to
+    /// swap the card they were shown.
+    Examine {
+        player: usize,
+        swap: bool,
+    },
+
+    /// A player chooses the first card of their hand.
+    Choose {
+        player: usize,
+        card: Card,
+    },
+}
+
+/// Implements commonly used functions performed on decisions.
+impl Decision {
+    /// Gets the player who made this decision.
+    pub fn player(&self) -> usize {
+        match self {
+            Decision::Action { player, .. } => *player,
+            Decision::Challenge { player, .. } => *player,
+            Decision::Block { player, .. } => *player,
+            Decision::LoseInfluence { player, .. } => *player,
+            Decision::Exchange { player, .. } => *player,
+            Decision::Show { player, .. } => *player,
+            Decision::Examine { player, .. } => *player,
+            Decision::Choose { player, .. } => *player,
+        }
+    }
+}
+
+/// Enumerates the errors that can occur while saving, loading, or replaying a record.
+#[derive(Debug)]
+pub enum RecordError {
+    /// The record could not be read or written.
+    Io (io::Error),
+
+    /// A line of the record could not be parsed.
+    Parse {
+        line: usize,
+        message: String,
+    },
+
+    /// The requested ply is past the end of the record.
+    OutOfRange {
+        ply: usize,
+        turns: usize,
+    },
+
+    /// Replaying the record did not reproduce the recorded game.
+    Inconsistent {
+        turn: usize,
+    },
+
+    /// The engine rejected a recorded decision.
+    Engine (EngineError),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Io (e) => write!(f, "I/O error: {}", e),
+            RecordError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            RecordError::OutOfRange { ply, turns } => write!(f, "ply {} is out of range (the record has {} turns)", ply, turns),
+            RecordError::Inconsistent { turn } => write!(f, "replay diverges from the record at turn {}", turn),
+            RecordError::Engine (e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+impl From<io::Error> for RecordError {
+    fn from(e: io::Error) -> Self {
+        RecordError::Io (e)
+    }
+}
+
+impl From<EngineError> for RecordError {
+    fn from(e: EngineError) -> Self {
+        RecordError::Engine (e)
+    }
+}
+
+/// Holds the complete record of a (possibly unfinished) game.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GameRecord {
+    /// The seed from which the game was generated.
+    pub seed: u64,
+
+    /// The rules under which the game was played.
+    pub rules: RuleSet,
+
+    /// The initial order of the deck, top first, or an empty list if the
+    /// deck was shuffled from the seed.
+    pub deck: Vec<Card>,
+
+    /// The hand initially dealt to each player.
+    pub deals: Vec<[Card; 2]>,
+
+    /// The decisions made while dealing, before the first turn.
+    pub setup: Vec<Decision>,
+
+    /// The decisions made during each turn, in the order they were made.
+    pub turns: Vec<Vec<Decision>>,
+}
+
+/// Implements commonly used functions performed on game records.
+impl GameRecord {
+    /// Constructs a new record with no turns.
+    pub fn new(seed: u64, rules: RuleSet, deck: Vec<Card>, deals: Vec<[Card; 2]>) -> Self {
+        Self {
+            seed,
+            rules,
+            deck,
+            deals,
+            setup: Vec::new(),
+            turns: Vec::new(),
+        }
+    }
+
+    /// Starts recording a new turn.
+    pub(crate) fn start_turn(&mut self) {
+        self.turns.push(Vec::new());
+    }
+
+    /// Records a decision in the current turn, or in the setup if the first
+    /// turn has not started yet.
+    pub(crate) fn push(&mut self, decision: Decision) {
+        match self.turns.last_mut() {
+            Some (turn) => turn.push(decision),
+            None => self.setup.push(decision),
+        }
+    }
+
+    /// Saves this record to the given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RecordError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// Loads a record from the given file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RecordError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Rebuilds the engine as it was after the given number of turns.
+    ///
+    /// The players of the returned engine replay the recorded decisions, so
+    /// the engine can be stepped forward with `Engine::turn` until the end of
+    /// the record.
+    pub fn engine_at(&self, ply: usize) -> Result<Engine, RecordError> {
+        if ply > self.turns.len() {
+            return Err(RecordError::OutOfRange {
+                ply,
+                turns: self.turns.len(),
+            });
+        }
+
+        let players: Vec<Box<dyn Player>> = (0..self.deals.len())
+            .map(|i| Box::new(ReplayPlayer::new(i, self)) as Box<dyn Player>)
+            .collect();
+
+        let mut engine = if self.deck.is_empty() {
+            Engine::with_seed(players, self.rules, self.seed)?
+        } else {
+            Engine::with_deck(players, self.rules, self.deck.clone(), self.seed)?
+        };
+        if engine.get_record().deals != self.deals || engine.get_record().setup != self.setup {
+            return Err(RecordError::Inconsistent {
+                turn: 0,
+            });
+        }
+
+        for turn in 0..ply {
+            engine.turn(false)?;
+
+            if engine.get_record().turns.get(turn) != self.turns.get(turn) {
+                return Err(RecordError::Inconsistent {
+                    turn: turn + 1,
+                });
+            }
+        }
+
+        Ok(engine)
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Contessa game record")?;
+        writeln!(f, "contessa {}", VERSION)?;
+        writeln!(f, "seed {}", self.seed)?;
+
+        let defaults = RuleSet::default().values();
+        for (rule, default) in self.rules.values().iter().zip(defaults.iter()) {
+            if rule != default {
+                writeln!(f, "rule {} {}", rule.0, rule.1)?;
+            }
+        }
+
+        if !self.deck.is_empty() {
+            write!(f, "deck")?;
+            for card in self.deck.iter() {
+                write!(f, " {}", card)?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "players {}", self.deals.len())?;
+
+        for (i, hand) in self.deals.iter().enumerate() {
+            writeln!(f, "deal {} {} {}", i, hand[0], hand[1])?;
+        }
+
+        for decision in self.setup.iter() {
+            write_decision(f, decision)?;
+        }
+
+        for turn in self.turns.iter() {
+            writeln!(f, "turn")?;
+
+            for decision in turn {
+                write_decision(f, decision)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = RecordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut seed = None;
+        let mut rules = RuleSet::default();
+        let mut deck = Vec::new();
+        let mut players = None;
+        let mut deals = Vec::new();
+        let mut setup = Vec::new();
+        let mut turns: Vec<Vec<Decision>> = Vec::new();
+
+        for (n, line) in s.lines().enumerate() {
+            let line_number = n + 1;
+            let error = |message: &str| RecordError::Parse {
+                line: line_number,
+                message: message.to_string(),
+            };
+
+            // Strip comments
+            let line = match line.find('#') {
+                Some (i) => &line[..i],
+                None => line,
+            };
+
+            let tokens = line.split_whitespace().collect::<Vec<&str>>();
+            let (keyword, args) = match tokens.split_first() {
+                Some ((keyword, args)) => (*keyword, args),
+                None => continue,
+            };
+
+            // Every decision applies to a player, given by the first argument
+            let player = || -> Result<usize, RecordError> {
+                let player = args.first()
+                    .and_then(|a| a.parse::<usize>().ok())
+                    .ok_or_else(|| error("expected a player number"))?;
+                match players {
+                    Some (p) if player < p => Ok(player),
+                    _ => Err(error("player number out of range")),
+                }
+            };
+
+            let decision = match keyword {
+                "contessa" => {
+                    match args.first().and_then(|a| a.parse::<u32>().ok()) {
+                        Some (VERSION) => continue,
+                        _ => return Err(error("unsupported record version")),
+                    }
+                },
+                "seed" => {
+                    seed = Some (args.first()
+                        .and_then(|a| a.parse::<u64>().ok())
+                        .ok_or_else(|| error("expected a seed"))?);
+                    continue;
+                },
+                "rule" => {
+                    let value = args.get(1)
+                        .and_then(|a| a.parse::<usize>().ok())
+                        .ok_or_else(|| error("expected `rule <name> <value>`"))?;
+                    if !rules.set(args[0], value) {
+                        return Err(error("unknown rule or value out of range"));
+                    }
+                    continue;
+                },
+                "deck" => {
+                    deck = args.iter()
+                        .map(|a| parse_card(a).ok_or_else(|| error("unknown card")))
+                        .collect::<Result<Vec<Card>, RecordError>>()?;
+                    continue;
+                },
+                "players" => {
+                    players = Some (args.first()
+                        .and_then(|a| a.parse::<usize>().ok())
+                        .ok_or_else(|| error("expected a number of players"))?);
+                    continue;
+                },
+                "deal" => {
+                    let player = player()?;
+                    if player != deals.len() || args.len() != 3 {
+                        return Err(error("expected `deal <player> <card> <card>` in player order"));
+                    }
+                    let hand = [
+                        parse_card(args[1]).ok_or_else(|| error("unknown card"))?,
+                        parse_card(args[2]).ok_or_else(|| error("unknown card"))?,
+                    ];
+                    deals.push(hand);
+                    continue;
+                },
+                "turn" => {
+                    turns.push(Vec::new());
+                    continue;
+                },
+                "action" => Decision::Action {
+                    player: player()?,
+                    action: args.get(1)
+                        .and_then(|a| parse_action(a))
+                        .ok_or_else(|| error("unknown action"))?,
+                },
+                "challenge" => Decision::Challenge {
+                    player: player()?,
+                    challenged: match args.get(1) {
+                        Some (&"yes") => true,
+                        Some (&"no") => false,
+                        _ => return Err(error("expected `yes` or `no`")),
+                    },
+                },
+                "block" => Decision::Block {
+                    player: player()?,
+                    card: match args.get(1) {
+                        Some (&"none") => None,
+                        Some (a) => Some (parse_card(a).ok_or_else(|| error("unknown card"))?),
+                        None => return Err(error("expected a card or `none`")),
+                    },
+                },
+                "lose" => Decision::LoseInfluence {
+                    player: player()?,
+                    card: args.get(1)
+                        .and_then(|a| parse_card(a))
+                        .ok_or_else(|| error("unknown card"))?,
+                },
+                "exchange" => Decision::Exchange {
+                    player: player()?,
+                    returned: args[1..].iter()
+                        .map(|a| parse_card(a).ok_or_else(|| error("unknown card")))
+                        .collect::<Result<Vec<Card>, RecordError>>()?,
+                },
+                "show" => Decision::Show {
+                    player: player()?,
+                    card: args.get(1)
+                        .and_then(|a| parse_card(a))
+                        .ok_or_else(|| error("unknown card"))?,
+                },
+                "examine" => Decision::Examine {
+                    player: player()?,
+                    swap: match args.get(1) {
+                        Some (&"yes") => true,
+                        Some (&"no") => false,
+                        _ => return Err(error("expected `yes` or `no`")),
+                    },
+                },
+                "choose" => Decision::Choose {
+                    player: player()?,
+                    card: args.get(1)
+                        .and_then(|a| parse_card(a))
+                        .ok_or_else(|| error("unknown card"))?,
+                },
+                _ => return Err(error("unknown keyword")),
+            };
+
+            match turns.last_mut() {
+                Some (turn) => turn.push(decision),
+                None => setup.push(decision),
+            }
+        }
+
+        let error = |message: &str| RecordError::Parse {
+            line: s.lines().count(),
+            message: message.to_string(),
+        };
+
+        let seed = seed.ok_or_else(|| error("missing `seed`"))?;
+        if players != Some (deals.len()) {
+            return Err(error("expected one `deal` per player"));
+        }
+
+        Ok(Self {
+            seed,
+            rules,
+            deck,
+            deals,
+            setup,
+            turns,
+        })
+    }
+}
+
+/// Writes a decision as a single line.
+fn write_decision(f: &mut fmt::Formatter<'_>, decision: &Decision) -> fmt::Result {
+    match decision {
+        Decision::Action { player, action } => writeln!(f, "action {} {}", player, format_action(*action)),
+        Decision::Challenge { player, challenged } => writeln!(f, "challenge {} {}", player, if *challenged { "yes" } else { "no" }),
+        Decision::Block { player, card: Some (card) } => writeln!(f, "block {} {}", player, card),
+        Decision::Block { player, card: None } => writeln!(f, "block {} none", player),
+        Decision::LoseInfluence { player, card } => writeln!(f, "lose {} {}", player, card),
+        Decision::Exchange { player, returned } => {
+            write!(f, "exchange {}", player)?;
+            for card in returned {
+                write!(f, " {}", card)?;
+            }
+            writeln!(f)
+        },
+        Decision::Show { player, card } => writeln!(f, "show {} {}", player, card),
+        Decision::Examine { player, swap } => writeln!(f, "examine {} {}", player, if *swap { "yes" } else { "no" }),
+        Decision::Choose { player, card } => writeln!(f, "choose {} {}", player, card),
+    }
+}
+
+/// Formats an action as a single token.
+fn format_action(action: Action) -> String {
+    match action {
+        Action::Income => "Income".to_string(),
+        Action::ForeignAid => "ForeignAid".to_string(),
+        Action::Coup (i) => format!("Coup({})", i),
+        Action::Tax => "Tax".to_string(),
+        Action::Assassinate (i) => format!("Assassinate({})", i),
+        Action::Exchange => "Exchange".to_string(),
+        Action::Steal (i) => format!("Steal({})", i),
+        Action::Pass => "Pass".to_string(),
+        Action::Convert (i) => format!("Convert({})", i),
+        Action::Embezzle => "Embezzle".to_string(),
+        Action::Examine (i) => format!("Examine({})", i),
+    }
+}
+
+/// Parses an action written by `format_action`.
+fn parse_action(token: &str) -> Option<Action> {
+    let (name, target) = match token.split_once('(') {
+        Some ((name, rest)) => (name, Some (rest.strip_suffix(')')?.parse::<usize>().ok()?)),
+        None => (token, None),
+    };
+
+    match (name, target) {
+        ("Income", None) => Some (Action::Income),
+        ("ForeignAid", None) => Some (Action::ForeignAid),
+        ("Coup", Some (i)) => Some (Action::Coup (i)),
+        ("Tax", None) => Some (Action::Tax),
+        ("Assassinate", Some (i)) => Some (Action::Assassinate (i)),
+        ("Exchange", None) => Some (Action::Exchange),
+        ("Steal", Some (i)) => Some (Action::Steal (i)),
+        ("Pass", None) => Some (Action::Pass),
+        ("Convert", Some (i)) => Some (Action::Convert (i)),
+        ("Embezzle", None) => Some (Action::Embezzle),
+        ("Examine", Some (i)) => Some (Action::Examine (i)),
+        _ => None,
+    }
+}
+
+/// Parses a card written by its `Display` implementation.
+fn parse_card(token: &str) -> Option<Card> {
+    match token {
+        "Duke" => Some (Card::Duke),
+        "Captain" => Some (Card::Captain),
+        "Ambassador" => Some (Card::Ambassador),
+        "Assassin" => Some (Card::Assassin),
+        "Contessa" => Some (Card::Contessa),
+        "Inquisitor" => Some (Card::Inquisitor),
+        "None" => Some (Card::None),
+        _ => None,
+    }
+}
+
+/// Holds the information of a player whose decisions are read from a record.
+#[derive(Clone, Debug)]
+struct ReplayPlayer {
+    hand: [Card; 2],
+    coins: u8,
+
+    /// The decisions this player made, in order.
+    decisions: Vec<Decision>,
+
+    /// The index of the next decision to replay.
+    next: usize,
+}
+
+impl ReplayPlayer {
+    /// Constructs a player that replays the decisions of Player `id` in the given record.
+    fn new(id: usize, record: &GameRecord) -> Self {
+        let decisions = record.setup.iter()
+            .chain(record.turns.iter().flatten())
+            .filter(|d| d.player() == id)
+            .cloned()
+            .collect();
+
+        Self {
+            hand: [Card::None, Card::None],
+            coins: 0,
+            decisions,
+            next: 0,
+        }
+    }
+
+    /// Gets the next recorded decision.
+    ///
+    /// Returns `None` once the record is exhausted.
+    fn next_decision(&mut self) -> Option<&Decision> {
+        self.next += 1;
+        self.decisions.get(self.next - 1)
+    }
+}
+
+impl Player for ReplayPlayer {
+    /// Replayed players have no metadata of their own.
+    fn get_metadata(&self) -> PlayerMetadata {
+        PlayerMetadata::Human
+    }
+
+    fn check(&self, card: Card) -> bool {
+        self.hand.contains(&card)
+    }
+
+    fn replace(&mut self, current: Card, new: Card) {
+        if self.hand[0] == current {
+            self.hand[0] = new;
+        } else {
+            self.hand[1] = new;
+        }
+    }
+
+    fn exchange(&mut self, _: &GameView, cards: &[Card]) -> Vec<Card> {
+        let returned = match self.next_decision() {
+            Some (Decision::Exchange { returned, .. }) => returned.clone(),
+            _ => cards.to_vec(),
+        };
+
+        // Keep whichever cards were not returned
+        let mut pool = self.hand.to_vec();
+        pool.extend_from_slice(cards);
+        for card in returned.iter() {
+            if let Some (i) = pool.iter().position(|c| c == card) {
+                pool.remove(i);
+            }
+        }
+        if pool.len() == 2 {
+            self.hand = [pool[0], pool[1]];
+        }
+
+        returned
+    }
+
+    fn get_coins(&self) -> u8 {
+        self.coins
+    }
+
+    fn compute_hands(&mut self, _: &GameView) {}
+
+    fn deal(&mut self, hand: [Card; 2]) {
+        self.hand = hand;
+    }
+
+    fn choose_influence(&mut self, _: &GameView, _: &[Card]) -> Card {
+        match self.next_decision() {
+            Some (Decision::Choose { card, .. }) => *card,
+            _ => Card::None,
+        }
+    }
+
+    fn gain_coins(&mut self, coins: u8) {
+        self.coins += coins;
+    }
+
+    fn lose_coins(&mut self, coins: u8) -> u8 {
+        let lost = coins.min(self.coins);
+        self.coins -= lost;
+        lost
+    }
+
+    fn lose_influence(&mut self, _: &GameView) -> Card {
+        let card = match self.next_decision() {
+            Some (Decision::LoseInfluence { card, .. }) => *card,
+            _ => Card::None,
+        };
+
+        if card != Card::None {
+            if self.hand[0] == card {
+                self.hand[0] = Card::None;
+            } else if self.hand[1] == card {
+                self.hand[1] = Card::None;
+            }
+        }
+
+        card
+    }
+
+    fn check_challenge(&mut self, _: &GameView, _: usize, _: Card) -> bool {
+        matches!(self.next_decision(), Some (Decision::Challenge { challenged: true, .. }))
+    }
+
+    fn check_block(&mut self, _: &GameView, _: Action) -> (bool, Card) {
+        match self.next_decision() {
+            Some (Decision::Block { card: Some (card), .. }) => (true, *card),
+            _ => (false, Card::None),
+        }
+    }
+
+    fn show_card(&mut self, _: &GameView, _: usize) -> Card {
+        match self.next_decision() {
+            Some (Decision::Show { card, .. }) => *card,
+            _ => Card::None,
+        }
+    }
+
+    fn check_examine(&mut self, _: &GameView, _: usize, _: Card) -> bool {
+        matches!(self.next_decision(), Some (Decision::Examine { swap: true, .. }))
+    }
+
+    fn is_eliminated(&self) -> bool {
+        self.hand == [Card::None, Card::None]
+    }
+
+    fn select_action(&mut self, _: &GameView) -> Action {
+        match self.next_decision() {
+            Some (Decision::Action { action, .. }) => *action,
+            _ => Action::Pass,
+        }
+    }
+}
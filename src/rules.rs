@@ -0,0 +1,245 @@
+//! Holds the numbers that govern a game of Coup.
+
+use crate::{
+    Card,
+    Action,
+};
+
+/// Holds the configurable rules of a game.
+///
+/// The engine enforces these rules and passes them to every player through
+/// `GameView::rules`.  `RuleSet::default()` gives the official rules for
+/// three to six players; `RuleSet::for_players` adjusts them for any other
+/// number of players.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct RuleSet {
+    /// The number of copies of each card in the deck.
+    pub copies: usize,
+
+    /// The number of coins each player starts with.
+    pub starting_coins: u8,
+
+    /// The number of coins gained by taking Income.
+    pub income: u8,
+
+    /// The number of coins gained by taking Foreign Aid.
+    pub foreign_aid: u8,
+
+    /// The number of coins gained by collecting Tax.
+    pub tax: u8,
+
+    /// The most coins that can be taken by a Steal.
+    pub steal: u8,
+
+    /// The number of coins a Coup costs.
+    pub coup_cost: u8,
+
+    /// The number of coins at which a player must Coup.
+    pub forced_coup: u8,
+
+    /// The number of coins an Assassination costs.
+    pub assassinate_cost: u8,
+
+    /// The number of turns after which `Engine::play` stops the game.
+    pub max_turns: usize,
+
+    /// Whether or not to play with the Reformation expansion (factions,
+    /// Convert, and Embezzle).
+    pub reformation: bool,
+
+    /// The number of coins it costs to Convert yourself.
+    pub convert_cost: u8,
+
+    /// The number of coins it costs to Convert another player.
+    pub convert_other_cost: u8,
+
+    /// Whether or not the Inquisitor replaces the Ambassador.
+    pub inquisitor: bool,
+
+    /// Whether or not to play the official two-player variant: the first
+    /// player starts with one coin fewer, and each player chooses one card
+    /// of their hand from a set holding one of each card.
+    pub head_to_head: bool,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self {
+            copies: 3,
+            starting_coins: 2,
+            income: 1,
+            foreign_aid: 2,
+            tax: 3,
+            steal: 2,
+            coup_cost: 7,
+            forced_coup: 10,
+            assassinate_cost: 3,
+            max_turns: 1000,
+            reformation: false,
+            convert_cost: 1,
+            convert_other_cost: 2,
+            inquisitor: false,
+            head_to_head: false,
+        }
+    }
+}
+
+/// Implements commonly used functions performed on rule sets.
+impl RuleSet {
+    /// Gets the official rules for the given number of players.
+    /// 
+    /// The deck holds three copies of each card for up to six players, four
+    /// for seven or eight players, and five for nine or more.  Two players
+    /// play head to head.
+    pub fn for_players(players: usize) -> Self {
+        Self {
+            copies: match players {
+                0..=6 => 3,
+                7..=8 => 4,
+                _ => 5,
+            },
+            head_to_head: players == 2,
+            ..Self::default()
+        }
+    }
+
+    /// Lists the cards in play, in the order used to build the deck.
+    pub fn cards(&self) -> [Card; 5] {
+        [
+            Card::Duke,
+            Card::Captain,
+            self.exchange_card(),
+            Card::Assassin,
+            Card::Contessa,
+        ]
+    }
+
+    /// Gets the card that allows a player to Exchange: the Ambassador, or
+    /// the Inquisitor if it replaces the Ambassador.
+    pub fn exchange_card(&self) -> Card {
+        if self.inquisitor {
+            Card::Inquisitor
+        } else {
+            Card::Ambassador
+        }
+    }
+
+    /// Gets the number of cards a player draws when taking Exchange.
+    /// 
+    /// The Ambassador draws two cards; the Inquisitor draws one.
+    pub fn exchange_draw(&self) -> usize {
+        if self.inquisitor {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Gets the card a player must claim to have in order to take an action.
+    /// 
+    /// Returns `Card::None` for actions that do not require a card (and
+    /// therefore cannot be challenged).
+    pub fn required_card(&self, action: Action) -> Card {
+        match action {
+            Action::Income => Card::None,
+            Action::ForeignAid => Card::None,
+            Action::Coup (_) => Card::None,
+            Action::Tax => Card::Duke,
+            Action::Assassinate (_) => Card::Assassin,
+            Action::Exchange => self.exchange_card(),
+            Action::Steal (_) => Card::Captain,
+            Action::Pass => Card::None,
+            Action::Convert (_) => Card::None,
+            Action::Embezzle => Card::None,
+            Action::Examine (_) => Card::Inquisitor,
+        }
+    }
+
+    /// Gets the card a player must claim *not* to have in order to take an
+    /// action.
+    /// 
+    /// Returns `Card::None` for every action but Embezzle.
+    pub fn denied_card(&self, action: Action) -> Card {
+        match action {
+            Action::Embezzle => Card::Duke,
+            _ => Card::None,
+        }
+    }
+
+    /// Gets the cards with which an action may be blocked.
+    pub fn blocking_cards(&self, action: Action) -> Vec<Card> {
+        match action {
+            Action::ForeignAid => vec![Card::Duke],
+            Action::Assassinate (_) => vec![Card::Contessa],
+            Action::Steal (_) => vec![Card::Captain, self.exchange_card()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Builds an unshuffled deck holding `copies` of each card.
+    pub fn deck(&self) -> Vec<Card> {
+        self.cards().iter()
+            .flat_map(|&card| std::iter::repeat_n(card, self.copies))
+            .collect()
+    }
+
+    /// Gets the total number of cards in the game.
+    pub fn deck_size(&self) -> usize {
+        self.cards().len()*self.copies
+    }
+
+    /// Lists the name and value of every rule, in a fixed order.
+    ///
+    /// The names are the field names of this struct; `reformation`,
+    /// `inquisitor`, and `head_to_head` are given as 0 or 1.
+    pub fn values(&self) -> [(&'static str, usize); 15] {
+        [
+            ("copies", self.copies),
+            ("starting_coins", self.starting_coins as usize),
+            ("income", self.income as usize),
+            ("foreign_aid", self.foreign_aid as usize),
+            ("tax", self.tax as usize),
+            ("steal", self.steal as usize),
+            ("coup_cost", self.coup_cost as usize),
+            ("forced_coup", self.forced_coup as usize),
+            ("assassinate_cost", self.assassinate_cost as usize),
+            ("max_turns", self.max_turns),
+            ("reformation", self.reformation as usize),
+            ("convert_cost", self.convert_cost as usize),
+            ("convert_other_cost", self.convert_other_cost as usize),
+            ("inquisitor", self.inquisitor as usize),
+            ("head_to_head", self.head_to_head as usize),
+        ]
+    }
+
+    /// Sets the rule with the given name (see `RuleSet::values`).
+    ///
+    /// Returns `false` if there is no such rule or the value is out of range.
+    pub fn set(&mut self, name: &str, value: usize) -> bool {
+        let coins = u8::try_from(value).ok();
+
+        match (name, coins) {
+            ("copies", _) => self.copies = value,
+            ("starting_coins", Some (coins)) => self.starting_coins = coins,
+            ("income", Some (coins)) => self.income = coins,
+            ("foreign_aid", Some (coins)) => self.foreign_aid = coins,
+            ("tax", Some (coins)) => self.tax = coins,
+            ("steal", Some (coins)) => self.steal = coins,
+            ("coup_cost", Some (coins)) => self.coup_cost = coins,
+            ("forced_coup", Some (coins)) => self.forced_coup = coins,
+            ("assassinate_cost", Some (coins)) => self.assassinate_cost = coins,
+            ("max_turns", _) => self.max_turns = value,
+            ("reformation", Some (0)) => self.reformation = false,
+            ("reformation", Some (1)) => self.reformation = true,
+            ("convert_cost", Some (coins)) => self.convert_cost = coins,
+            ("convert_other_cost", Some (coins)) => self.convert_other_cost = coins,
+            ("inquisitor", Some (0)) => self.inquisitor = false,
+            ("inquisitor", Some (1)) => self.inquisitor = true,
+            ("head_to_head", Some (0)) => self.head_to_head = false,
+            ("head_to_head", Some (1)) => self.head_to_head = true,
+            _ => return false,
+        }
+
+        true
+    }
+}
@@ -0,0 +1,896 @@
+//! Implements a player that searches ahead with Information-Set Monte Carlo
+//! Tree Search (ISMCTS).
+
+use std::{
+    collections::{
+        HashMap,
+        hash_map::DefaultHasher,
+    },
+    hash::{
+        Hash,
+        Hasher,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use rand::{
+    Rng,
+    SeedableRng,
+    rngs::StdRng,
+    seq::SliceRandom,
+};
+
+use crate::{
+    Card,
+    Action,
+    Player,
+    PlayerMetadata,
+    GameEvent,
+    GameView,
+    Engine,
+    Belief,
+    Distribution,
+};
+
+/// The exploration constant of the UCB1 formula used to pick moves in the tree.
+const EXPLORATION: f64 = 0.7;
+
+/// The number of turns a simulation may run before it is scored as it stands.
+const SIMULATED_TURNS: usize = 100;
+
+/// The probability that a simulated player makes a risky move (a bluff or a
+/// challenge) when it is not sure of it.
+const RISK: f64 = 0.1;
+
+/// Limits how much a `Searcher` thinks about each decision.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Budget {
+    /// Run this many simulations.  The decisions of a searcher with this
+    /// budget depend only on its seed.
+    Iterations (usize),
+
+    /// Run simulations until this much time has passed.
+    Time (Duration),
+}
+
+/// Enumerates the decisions a `Searcher` searches.
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+enum Kind {
+    Action,
+    Challenge,
+    Block,
+    Influence,
+}
+
+/// Enumerates the possible answers to each kind of decision.
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+enum Choice {
+    Act (Action),
+    Challenge (bool),
+    Block (Option<Card>),
+    Lose (Card),
+}
+
+/// Holds the statistics of one information set in the search tree.
+#[derive(Clone, Debug)]
+struct Node {
+    /// The number of simulations that passed through this node.
+    visits: f64,
+
+    /// The number of visits and the total reward of each option.
+    children: Vec<(f64, f64)>,
+}
+
+/// Implements commonly used functions performed on nodes.
+impl Node {
+    /// Constructs a node with the given number of options, none of them tried.
+    fn new(options: usize) -> Self {
+        Self {
+            visits: 0.0,
+            children: vec![(0.0, 0.0); options],
+        }
+    }
+
+    /// Selects an option: any untried one first, then the one with the best
+    /// upper confidence bound.
+    fn select(&self, rng: &mut StdRng) -> usize {
+        let untried = (0..self.children.len())
+            .filter(|&i| self.children[i].0 == 0.0)
+            .collect::<Vec<usize>>();
+        if let Some (&i) = untried.choose(rng) {
+            return i;
+        }
+
+        let bound = |&(visits, reward): &(f64, f64)| reward/visits + EXPLORATION*(self.visits.ln()/visits).sqrt();
+
+        // Note: it's OK to use `Option::unwrap` here because we know the
+        // bounds are finite (every option has been visited)
+        (0..self.children.len())
+            .max_by(|&a, &b| bound(&self.children[a]).partial_cmp(&bound(&self.children[b])).unwrap())
+            .unwrap_or(0)
+    }
+}
+
+/// Holds the state of a search, shared by the searcher and every simulated
+/// player.
+#[derive(Debug)]
+struct Search {
+    /// The statistics of every information set reached so far.
+    nodes: HashMap<u64, Node>,
+
+    /// The history of the real game at the decision being searched.
+    history: Vec<GameEvent>,
+
+    /// The index of the history at which the current turn starts.
+    start: usize,
+
+    /// The searcher's hand at the decision being searched.
+    hand: [Card; 2],
+
+    /// The kind of decision being searched.
+    kind: Kind,
+
+    /// The information sets and options the searcher chose in the current
+    /// simulation.
+    path: Vec<(u64, usize)>,
+
+    /// Whether or not the current simulation has reached the decision being
+    /// searched.
+    branched: bool,
+
+    /// Whether or not a node has been added to the tree in the current
+    /// simulation.
+    expanded: bool,
+
+    /// Whether or not the current simulation has turned out to be
+    /// inconsistent with the real game.
+    rejected: bool,
+}
+
+/// Holds the information of a player in a simulated game.
+///
+/// Until the simulation reaches the decision being searched, every
+/// simulated player repeats the decisions made in the real game, as far as
+/// the public history shows them.  After that, the searcher's decisions
+/// follow the search tree and everyone else plays a simple policy.
+#[derive(Clone, Debug)]
+struct Simulated {
+    id: usize,
+    hand: [Card; 2],
+    coins: u8,
+
+    /// Whether or not this is the searching player.
+    searcher: bool,
+
+    /// Holds the state of the search.
+    search: Arc<Mutex<Search>>,
+
+    /// Drives every random decision this player makes.
+    rng: StdRng,
+}
+
+/// Holds the information and performs the actions of a player that picks
+/// its moves by simulating the rest of the game.
+///
+/// Each simulation deals the other players hands drawn from this player's
+/// beliefs (see `Belief`), replays the current turn up to the decision at
+/// hand, and plays the game out.  Actions, challenges, blocks, and influence
+/// losses are chosen from a search tree over this player's information sets;
+/// the other decisions follow simple rules.
+#[derive(Clone, Debug)]
+pub struct Searcher {
+    /// Stores this player's ID.
+    id: usize,
+
+    /// Holds the player's hand.
+    hand: [Card; 2],
+
+    /// Provides the number of coins the player has.
+    coins: u8,
+
+    /// Limits how much this player thinks about each decision.
+    budget: Budget,
+
+    /// Holds what this player believes about the other players' hands.
+    beliefs: Belief,
+
+    /// Holds this player's view of the game at the start of the current turn.
+    start: Option<GameView>,
+
+    /// Drives every random decision this player makes.
+    rng: StdRng,
+}
+
+impl Player for Searcher {
+    /// Gets the metadata of the given player.
+    fn get_metadata(&self) -> PlayerMetadata {
+        PlayerMetadata::Searcher
+    }
+
+    /// Checks whether or not a player has a given card.
+    fn check(&self, card: Card) -> bool {
+        self.hand.contains(&card)
+    }
+
+    /// Replaces one of the cards in this player's hand with the given card.
+    ///
+    /// Note: call this function only when you know for sure that the outgoing
+    /// card exists in this player's hand or you will mess things up.
+    fn replace(&mut self, current: Card, new: Card) {
+        replace(&mut self.hand, current, new);
+    }
+
+    /// Exchanges cards (used on `Ambassador`).
+    ///
+    /// Right now, this keeps the most valuable cards (see `value`).
+    fn exchange(&mut self, _: &GameView, cards: &[Card]) -> Vec<Card> {
+        let mut options = live(&self.hand);
+        let influence = options.len();
+        options.extend_from_slice(cards);
+        options.sort_by_key(|card| std::cmp::Reverse(value(*card)));
+
+        let returned = options.split_off(influence);
+        self.hand = [Card::None, Card::None];
+        for (i, card) in options.into_iter().enumerate() {
+            self.hand[i] = card;
+        }
+
+        returned
+    }
+
+    /// Gets the number of coins this player has.
+    fn get_coins(&self) -> u8 {
+        self.coins
+    }
+
+    /// Updates this player's beliefs at the start of a turn.
+    fn compute_hands(&mut self, view: &GameView) {
+        self.beliefs.update(view);
+        self.start = Some (view.clone());
+    }
+
+    /// Deals the given cards to the player.
+    fn deal(&mut self, hand: [Card; 2]) {
+        self.hand = hand;
+    }
+
+    /// Chooses the first card of this player's hand.
+    ///
+    /// Right now, this picks the most valuable card (see `value`).
+    fn choose_influence(&mut self, _: &GameView, cards: &[Card]) -> Card {
+        cards.iter()
+            .copied()
+            .max_by_key(|card| value(*card))
+            .unwrap_or(Card::None)
+    }
+
+    /// Gains the number of coins specified.
+    fn gain_coins(&mut self, coins: u8) {
+        self.coins += coins;
+    }
+
+    /// Loses the number of coins specified.
+    fn lose_coins(&mut self, coins: u8) -> u8 {
+        let lost = coins.min(self.coins);
+        self.coins -= lost;
+        lost
+    }
+
+    /// Forces the player to lose one influence.
+    fn lose_influence(&mut self, view: &GameView) -> Card {
+        let mut options = live(&self.hand);
+        options.dedup();
+
+        let card = match self.search(view, Kind::Influence, options.into_iter().map(Choice::Lose).collect()) {
+            Choice::Lose (card) => card,
+            _ => Card::None,
+        };
+
+        replace(&mut self.hand, card, Card::None);
+        card
+    }
+
+    /// Asks this player if he challenges a claim.
+    ///
+    /// Returns `true` if the player challenges and `false` otherwise.
+    fn check_challenge(&mut self, view: &GameView, _: usize, card: Card) -> bool {
+        if card == Card::None || self.is_eliminated() {
+            return false;
+        }
+
+        let options = vec![Choice::Challenge (false), Choice::Challenge (true)];
+        self.search(view, Kind::Challenge, options) == Choice::Challenge (true)
+    }
+
+    /// Asks this player if he blocks an action.
+    ///
+    /// Returns `true` if the player blocks and `false` otherwise.  Also returns
+    /// the card with which the player blocks.
+    fn check_block(&mut self, view: &GameView, action: Action) -> (bool, Card) {
+        if self.is_eliminated() {
+            return (false, Card::None);
+        }
+
+        let options = std::iter::once(None)
+            .chain(view.rules.blocking_cards(action).into_iter().map(Some))
+            .map(Choice::Block)
+            .collect();
+
+        match self.search(view, Kind::Block, options) {
+            Choice::Block (Some (card)) => (true, card),
+            _ => (false, Card::None),
+        }
+    }
+
+    /// Shows a card to a player examining this one.
+    ///
+    /// Right now, this shows the least valuable card (see `value`).
+    fn show_card(&mut self, _: &GameView, _: usize) -> Card {
+        live(&self.hand).into_iter()
+            .min_by_key(|card| value(*card))
+            .unwrap_or(Card::None)
+    }
+
+    /// Decides whether or not to force an examined player to swap a card.
+    ///
+    /// Right now, this swaps away any card that can be used to take coins
+    /// or influence from other players.
+    fn check_examine(&mut self, _: &GameView, _: usize, card: Card) -> bool {
+        matches!(card, Card::Duke | Card::Assassin | Card::Captain)
+    }
+
+    /// Checks whether or not a player is still in the game.
+    fn is_eliminated(&self) -> bool {
+        self.hand == [Card::None, Card::None]
+    }
+
+    /// Select an action based on actions available.
+    fn select_action(&mut self, view: &GameView) -> Action {
+        let options = view.legal_actions.iter()
+            .copied()
+            .map(Choice::Act)
+            .collect();
+
+        match self.search(view, Kind::Action, options) {
+            Choice::Act (action) => action,
+            _ => Action::Pass,
+        }
+    }
+
+    /// Reseeds this player's random number generator.
+    fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+/// Implements necessary behaviors of a searching player.
+impl Searcher {
+    /// Constructs a new player with the given ID and budget.
+    pub fn new(id: usize, budget: Budget) -> Self {
+        Self::from_rng(id, budget, StdRng::from_entropy())
+    }
+
+    /// Constructs a new player whose decisions are derived from the given
+    /// seed (as long as its budget is a number of iterations).
+    pub fn with_seed(id: usize, budget: Budget, seed: u64) -> Self {
+        Self::from_rng(id, budget, StdRng::seed_from_u64(seed))
+    }
+
+    /// Constructs a new player from the given random number generator.
+    fn from_rng(id: usize, budget: Budget, rng: StdRng) -> Self {
+        Self {
+            id,
+            hand: [Card::None, Card::None],
+            coins: 0,
+            budget,
+            beliefs: Belief::new(),
+            start: None,
+            rng,
+        }
+    }
+
+    /// Gets the budget of this player.
+    pub fn get_budget(&self) -> Budget {
+        self.budget
+    }
+
+    /// Picks one of the given options by simulating the rest of the game.
+    ///
+    /// Falls back on the simple policy of the simulated players if there is
+    /// nothing to search, or if no simulation reaches the decision.
+    fn search(&mut self, view: &GameView, kind: Kind, options: Vec<Choice>) -> Choice {
+        let fallback = |rng: &mut StdRng| policy(view, rng, kind, &options);
+
+        let start = match &self.start {
+            Some (start) if options.len() > 1
+                && start.turn == view.turn
+                && view.history.starts_with(&start.history) => start.clone(),
+            _ => return fallback(&mut self.rng),
+        };
+
+        let root = key(kind, view, &options);
+        let search = Arc::new(Mutex::new(Search {
+            nodes: HashMap::new(),
+            history: view.history.clone(),
+            start: start.history.len(),
+            hand: view.hand,
+            kind,
+            path: Vec::new(),
+            branched: false,
+            expanded: false,
+            rejected: false,
+        }));
+
+        let began = Instant::now();
+        let mut iterations = 0;
+        loop {
+            match self.budget {
+                Budget::Iterations (n) if iterations >= n => break,
+                Budget::Time (limit) if began.elapsed() >= limit => break,
+                _ => iterations += 1,
+            }
+
+            let hands = self.determinize(&start);
+            let players = (0..start.players.len())
+                .map(|i| Box::new(Simulated {
+                    id: i,
+                    hand: [Card::None, Card::None],
+                    coins: 0,
+                    searcher: i == self.id,
+                    search: search.clone(),
+                    rng: StdRng::seed_from_u64(self.rng.gen()),
+                }) as Box<dyn Player>)
+                .collect();
+
+            let mut engine = match Engine::from_view(&start, players, hands, self.rng.gen()) {
+                Ok(engine) => engine,
+                Err(_) => continue,
+            };
+
+            {
+                // Note: it's OK to use `Result::unwrap` here because no
+                // thread holding this lock can panic
+                let mut search = search.lock().unwrap();
+                search.path.clear();
+                search.branched = false;
+                search.expanded = false;
+                search.rejected = false;
+            }
+
+            // Play the current turn, which must reach the decision at hand
+            // without contradicting the real game
+            let mut winner = engine.turn(false);
+            {
+                let search = search.lock().unwrap();
+                if winner.is_err() || search.rejected || !search.branched {
+                    continue;
+                }
+            }
+
+            // Play the rest of the game
+            for _ in 0..SIMULATED_TURNS {
+                match winner {
+                    Ok(None) => winner = engine.turn(false),
+                    _ => break,
+                }
+            }
+
+            let reward = match winner {
+                Ok(Some (i)) => if i == self.id { 1.0 } else { 0.0 },
+                Ok(None) => if engine.is_eliminated(self.id) {
+                    0.0
+                } else {
+                    // Share the game among the players left standing
+                    let left = (0..start.players.len()).filter(|&i| !engine.is_eliminated(i)).count();
+                    1.0/left as f64
+                },
+                Err(_) => continue,
+            };
+
+            let mut search = search.lock().unwrap();
+            let path = std::mem::take(&mut search.path);
+            for (key, i) in path {
+                if let Some (node) = search.nodes.get_mut(&key) {
+                    node.visits += 1.0;
+                    node.children[i].0 += 1.0;
+                    node.children[i].1 += reward;
+                }
+            }
+        }
+
+        // Pick the option simulated most often
+        let search = search.lock().unwrap();
+        match search.nodes.get(&root) {
+            Some (node) if node.visits > 0.0 => {
+                // Note: it's OK to use `Option::unwrap` here because we
+                // know the root has one child per option
+                let best = (0..options.len())
+                    .max_by(|&a, &b| node.children[a].0.partial_cmp(&node.children[b].0).unwrap())
+                    .unwrap();
+
+                options[best]
+            },
+            _ => fallback(&mut self.rng),
+        }
+    }
+
+    /// Deals every other player a hand drawn from this player's beliefs,
+    /// such that no more copies of a card are dealt than are unseen.
+    fn determinize(&mut self, start: &GameView) -> Vec<[Card; 2]> {
+        let distribution = Distribution::private(start);
+        let mut unseen = start.rules.cards().iter()
+            .flat_map(|&card| std::iter::repeat_n(card, distribution.count(card)))
+            .collect::<Vec<Card>>();
+
+        let mut hands = vec![[Card::None, Card::None]; start.players.len()];
+        hands[self.id] = start.hand;
+
+        let mut others = (0..start.players.len())
+            .filter(|&i| i != self.id && !start.players[i].is_eliminated())
+            .collect::<Vec<usize>>();
+        others.shuffle(&mut self.rng);
+
+        for i in others {
+            let influence = start.players[i].influence();
+
+            // Weigh each believed hand by its odds of being drawn from the
+            // cards not yet dealt, relative to all of the unseen cards
+            let candidates = self.beliefs.hands(i).into_iter()
+                .filter(|(hand, _)| hand.len() == influence)
+                .map(|(hand, probability)| {
+                    let left = ways(&unseen, &hand)/distribution.ways(&hand).max(1.0);
+                    (hand, probability*left)
+                })
+                .collect::<Vec<(Vec<Card>, f64)>>();
+
+            let hand = match candidates.choose_weighted(&mut self.rng, |(_, weight)| *weight) {
+                Ok((hand, _)) => hand.clone(),
+                Err(_) => {
+                    // Nothing believed fits, so deal from the unseen cards
+                    unseen.shuffle(&mut self.rng);
+                    unseen.iter().take(influence).copied().collect()
+                },
+            };
+
+            for (j, card) in hand.into_iter().enumerate() {
+                if let Some (k) = unseen.iter().position(|c| *c == card) {
+                    unseen.remove(k);
+                }
+                hands[i][j] = card;
+            }
+        }
+
+        hands
+    }
+}
+
+impl Player for Simulated {
+    /// Simulated players have no metadata of their own.
+    fn get_metadata(&self) -> PlayerMetadata {
+        PlayerMetadata::Searcher
+    }
+
+    /// Checks whether or not a player has a given card.
+    fn check(&self, card: Card) -> bool {
+        self.hand.contains(&card)
+    }
+
+    /// Replaces one of the cards in this player's hand with the given card.
+    fn replace(&mut self, current: Card, new: Card) {
+        replace(&mut self.hand, current, new);
+    }
+
+    /// Exchanges cards at random.
+    fn exchange(&mut self, _: &GameView, cards: &[Card]) -> Vec<Card> {
+        let mut options = live(&self.hand);
+        let influence = options.len();
+        options.extend_from_slice(cards);
+        options.shuffle(&mut self.rng);
+
+        let returned = options.split_off(influence);
+        self.hand = [Card::None, Card::None];
+        for (i, card) in options.into_iter().enumerate() {
+            self.hand[i] = card;
+        }
+
+        returned
+    }
+
+    /// Gets the number of coins this player has.
+    fn get_coins(&self) -> u8 {
+        self.coins
+    }
+
+    /// Simulated players keep no beliefs.
+    fn compute_hands(&mut self, _: &GameView) {}
+
+    /// Deals the given cards to the player.
+    fn deal(&mut self, hand: [Card; 2]) {
+        self.hand = hand;
+    }
+
+    /// Chooses the first card of this player's hand at random.
+    fn choose_influence(&mut self, _: &GameView, cards: &[Card]) -> Card {
+        cards.choose(&mut self.rng).copied().unwrap_or(Card::None)
+    }
+
+    /// Gains the number of coins specified.
+    fn gain_coins(&mut self, coins: u8) {
+        self.coins += coins;
+    }
+
+    /// Loses the number of coins specified.
+    fn lose_coins(&mut self, coins: u8) -> u8 {
+        let lost = coins.min(self.coins);
+        self.coins -= lost;
+        lost
+    }
+
+    /// Forces the player to lose one influence.
+    fn lose_influence(&mut self, view: &GameView) -> Card {
+        let mut options = live(&self.hand);
+        options.dedup();
+
+        let card = match self.decide(view, Kind::Influence, options.into_iter().map(Choice::Lose).collect()) {
+            Choice::Lose (card) => card,
+            _ => Card::None,
+        };
+
+        replace(&mut self.hand, card, Card::None);
+        card
+    }
+
+    /// Asks this player if he challenges a claim.
+    fn check_challenge(&mut self, view: &GameView, _: usize, card: Card) -> bool {
+        if card == Card::None {
+            return false;
+        }
+
+        let options = vec![Choice::Challenge (false), Choice::Challenge (true)];
+        self.decide(view, Kind::Challenge, options) == Choice::Challenge (true)
+    }
+
+    /// Asks this player if he blocks an action.
+    fn check_block(&mut self, view: &GameView, action: Action) -> (bool, Card) {
+        let options = std::iter::once(None)
+            .chain(view.rules.blocking_cards(action).into_iter().map(Some))
+            .map(Choice::Block)
+            .collect();
+
+        match self.decide(view, Kind::Block, options) {
+            Choice::Block (Some (card)) => (true, card),
+            _ => (false, Card::None),
+        }
+    }
+
+    /// Shows a random card to a player examining this one.
+    fn show_card(&mut self, _: &GameView, _: usize) -> Card {
+        live(&self.hand).choose(&mut self.rng).copied().unwrap_or(Card::None)
+    }
+
+    /// Decides whether or not to force an examined player to swap a card.
+    ///
+    /// Before the decision being searched, this repeats the real game.
+    fn check_examine(&mut self, view: &GameView, _: usize, card: Card) -> bool {
+        // Note: it's OK to use `Result::unwrap` here because no thread
+        // holding this lock can panic
+        let search = self.search.lock().unwrap();
+        match search.history.get(view.history.len()) {
+            Some (GameEvent::CardSwapped { .. }) if !search.branched => true,
+            Some (_) if !search.branched => false,
+            _ => matches!(card, Card::Duke | Card::Assassin | Card::Captain),
+        }
+    }
+
+    /// Checks whether or not a player is still in the game.
+    fn is_eliminated(&self) -> bool {
+        self.hand == [Card::None, Card::None]
+    }
+
+    /// Select an action based on actions available.
+    fn select_action(&mut self, view: &GameView) -> Action {
+        let options = view.legal_actions.iter()
+            .copied()
+            .map(Choice::Act)
+            .collect();
+
+        match self.decide(view, Kind::Action, options) {
+            Choice::Act (action) => action,
+            _ => Action::Pass,
+        }
+    }
+
+    /// Reseeds this player's random number generator.
+    fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+/// Implements the decisions of a simulated player.
+impl Simulated {
+    /// Makes a decision in a simulated game.
+    fn decide(&mut self, view: &GameView, kind: Kind, options: Vec<Choice>) -> Choice {
+        // Note: it's OK to use `Result::unwrap` here because no thread
+        // holding this lock can panic
+        let mut search = self.search.lock().unwrap();
+        let seen = view.history.len();
+
+        if !search.branched {
+            // The simulation must follow the real game exactly
+            if seen > search.history.len() || view.history[search.start..] != search.history[search.start..seen] {
+                search.rejected = true;
+            } else if seen < search.history.len() {
+                return repeat(self.id, kind, &search.history[seen], &options);
+            } else if self.searcher && kind == search.kind {
+                search.branched = true;
+                if self.hand != search.hand {
+                    search.rejected = true;
+                }
+            }
+        }
+
+        if !self.searcher || !search.branched || search.rejected || options.len() < 2 {
+            return policy(view, &mut self.rng, kind, &options);
+        }
+
+        // Follow the tree, adding at most one node per simulation
+        let key = key(kind, view, &options);
+        let i = match search.nodes.get(&key) {
+            Some (node) => node.select(&mut self.rng),
+            None if !search.expanded => {
+                search.nodes.insert(key, Node::new(options.len()));
+                search.expanded = true;
+                self.rng.gen_range(0..options.len())
+            },
+            None => return policy(view, &mut self.rng, kind, &options),
+        };
+
+        search.path.push((key, i));
+        options[i]
+    }
+}
+
+/// Repeats the decision a player made in the real game, as shown by the
+/// event that followed it.
+fn repeat(id: usize, kind: Kind, next: &GameEvent, options: &[Choice]) -> Choice {
+    match (kind, next) {
+        (Kind::Action, GameEvent::ActionDeclared { action, .. }) => Choice::Act (*action),
+        (Kind::Challenge, GameEvent::Challenge { challenger, .. })
+        | (Kind::Challenge, GameEvent::DenialChallenge { challenger, .. })
+        | (Kind::Challenge, GameEvent::BlockChallenged { challenger, .. }) => Choice::Challenge (*challenger == id),
+        (Kind::Challenge, _) => Choice::Challenge (false),
+        (Kind::Block, GameEvent::Block { blocker, card, .. }) if *blocker == id => Choice::Block (Some (*card)),
+        (Kind::Block, _) => Choice::Block (None),
+        (Kind::Influence, GameEvent::InfluenceLost { player, card }) if *player == id => Choice::Lose (*card),
+        _ => options.first().copied().unwrap_or(Choice::Challenge (false)),
+    }
+}
+
+/// Makes a decision with the simple policy used outside of the search tree.
+///
+/// The policy mostly acts and blocks honestly, Coups when it can, and
+/// challenges claims it knows to be false, but bluffs and challenges at
+/// random every so often.
+fn policy(view: &GameView, rng: &mut StdRng, kind: Kind, options: &[Choice]) -> Choice {
+    let risky = rng.gen_bool(RISK);
+
+    let choice = match kind {
+        Kind::Action => {
+            let honest = options.iter()
+                .copied()
+                .filter(|choice| match choice {
+                    Choice::Act (action) => {
+                        let required = view.rules.required_card(*action);
+                        let denied = view.rules.denied_card(*action);
+                        (required == Card::None || view.hand.contains(&required))
+                            && (denied == Card::None || !view.hand.contains(&denied))
+                    },
+                    _ => false,
+                })
+                .collect::<Vec<Choice>>();
+            let coups = options.iter()
+                .copied()
+                .filter(|choice| matches!(choice, Choice::Act (Action::Coup (_))))
+                .collect::<Vec<Choice>>();
+
+            if !coups.is_empty() && !risky {
+                coups.choose(rng).copied()
+            } else if !honest.is_empty() && !risky {
+                honest.choose(rng).copied()
+            } else {
+                options.choose(rng).copied()
+            }
+        },
+        Kind::Challenge => {
+            // Challenge any claim to a card whose every copy is accounted for
+            let card = match view.current {
+                Some (Action::Embezzle) => Card::None,
+                _ => view.history.iter().rev().find_map(|event| match event {
+                    GameEvent::ActionDeclared { action, .. } => Some (view.rules.required_card(*action)),
+                    GameEvent::Block { card, .. } => Some (*card),
+                    _ => None,
+                }).unwrap_or(Card::None),
+            };
+            let seen = view.killed().iter().chain(view.hand.iter()).filter(|c| **c == card).count();
+
+            Some (Choice::Challenge (risky || (card != Card::None && seen >= view.rules.copies)))
+        },
+        Kind::Block => {
+            let honest = options.iter()
+                .copied()
+                .find(|choice| matches!(choice, Choice::Block (Some (card)) if view.hand.contains(card)));
+
+            match honest {
+                Some (choice) => Some (choice),
+                None if risky => options.last().copied(),
+                None => Some (Choice::Block (None)),
+            }
+        },
+        Kind::Influence => options.choose(rng).copied(),
+    };
+
+    choice.unwrap_or(options.first().copied().unwrap_or(Choice::Challenge (false)))
+}
+
+/// Gets the key of the information set in which a player faces a decision:
+/// everything that player knows, and the options they have.
+fn key(kind: Kind, view: &GameView, options: &[Choice]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    kind.hash(&mut hasher);
+    view.id.hash(&mut hasher);
+    view.hand.hash(&mut hasher);
+    view.history.hash(&mut hasher);
+    options.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Ranks cards by how useful they are to keep.
+pub(crate) fn value(card: Card) -> u8 {
+    match card {
+        Card::Duke => 5,
+        Card::Assassin => 4,
+        Card::Captain => 3,
+        Card::Inquisitor => 3,
+        Card::Contessa => 2,
+        Card::Ambassador => 1,
+        Card::None => 0,
+    }
+}
+
+/// Lists the cards left in a hand.
+pub(crate) fn live(hand: &[Card; 2]) -> Vec<Card> {
+    hand.iter()
+        .copied()
+        .filter(|card| *card != Card::None)
+        .collect()
+}
+
+/// Replaces one copy of a card in a hand.
+pub(crate) fn replace(hand: &mut [Card; 2], current: Card, new: Card) {
+    if hand[0] == current {
+        hand[0] = new;
+    } else if hand[1] == current {
+        hand[1] = new;
+    }
+}
+
+/// Counts the ways to draw a hand from the given cards, in any order.
+fn ways(cards: &[Card], hand: &[Card]) -> f64 {
+    hand.iter()
+        .enumerate()
+        .map(|(i, card)| {
+            // The k-th copy of a card in the hand may be any of the copies
+            // left, but the copies may be drawn in any order
+            let k = hand[..i].iter().filter(|c| *c == card).count();
+            let count = cards.iter().filter(|c| *c == card).count();
+            count.saturating_sub(k) as f64/(k + 1) as f64
+        })
+        .product()
+}
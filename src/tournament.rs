@@ -0,0 +1,442 @@
+//! Implements round-robin tournaments between computer players.
+//!
+//! Every group of entrants that fits at a table plays once from each seat
+//! rotation, so no entrant gains from always moving first.  Each game is
+//! scored as a set of head-to-head results, in which a player beats every
+//! player eliminated before it, and those results are fitted to a
+//! Bradley-Terry model on the Elo scale.  Confidence intervals come from
+//! refitting the model to games resampled with replacement.
+
+use std::{
+    fmt,
+    sync::Arc,
+};
+
+use rand::{
+    Rng,
+    SeedableRng,
+    rngs::StdRng,
+};
+
+use crate::{
+    Agent,
+    Batch,
+    CfrAgent,
+    Engine,
+    EngineError,
+    GameEvent,
+    Player,
+    PlayerMetadata,
+    RuleSet,
+    StrategyTable,
+};
+
+/// The rating of an entrant as strong as the reference player.
+const BASE: f64 = 1500.0;
+
+/// The rating difference at which the stronger player is ten times as
+/// likely to win a head-to-head result.
+const SCALE: f64 = 400.0;
+
+/// The number of iterations allowed when fitting ratings.
+const ITERATIONS: usize = 1000;
+
+/// Enumerates the kinds of player that can enter a tournament.
+// A tournament holds only a handful of entrants, so genomes are not boxed
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+pub enum Entrant {
+    /// An `Agent` with the given genome, which must be a
+    /// `PlayerMetadata::Computer`.
+    Genome (PlayerMetadata),
+
+    /// A `CfrAgent` following the given strategy table.
+    Strategy (Arc<StrategyTable>),
+}
+
+/// Implements the seating of entrants.
+impl Entrant {
+    /// Constructs a player for this entrant in the given seat.
+    fn player(&self, id: usize, opponents: usize) -> Box<dyn Player> {
+        match self {
+            Entrant::Genome (metadata) => Box::new(Agent::from_metadata(id, opponents, *metadata)),
+            Entrant::Strategy (table) => Box::new(CfrAgent::new(id, Arc::clone(table))),
+        }
+    }
+}
+
+/// Holds the settings of a tournament.
+#[derive(Clone, Copy, Debug)]
+pub struct TournamentConfig {
+    /// The number of players at each table.
+    pub players: usize,
+
+    /// The number of times each table plays every seat rotation.
+    pub rounds: usize,
+
+    /// The rules the games are played by.
+    pub rules: RuleSet,
+
+    /// The number of resampled sets of games used to find the confidence
+    /// intervals.
+    pub resamples: usize,
+
+    /// The number of threads the games are spread across.  A tournament
+    /// gives the same results whatever the number of threads.
+    pub threads: usize,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        Self {
+            players: 4,
+            rounds: 1,
+            rules: RuleSet::default(),
+            resamples: 200,
+            threads: Batch::new().get_threads(),
+        }
+    }
+}
+
+/// Holds how one entrant fared in a tournament.
+#[derive(Clone, Debug)]
+pub struct Standing {
+    /// The name of the entrant.
+    pub name: String,
+
+    /// The rating of the entrant.  An entrant with a rating 400 points above
+    /// another is expected to outlast it ten times as often.
+    pub rating: f64,
+
+    /// The lower end of the 95% confidence interval of the rating.
+    pub low: f64,
+
+    /// The upper end of the 95% confidence interval of the rating.
+    pub high: f64,
+
+    /// The number of games the entrant played.
+    pub games: usize,
+
+    /// The share of its games the entrant won.  A game that reaches
+    /// `RuleSet::max_turns` is shared equally among the players left
+    /// standing.
+    pub wins: f64,
+}
+
+/// Lists the entrants of a tournament from the highest rated down.
+#[derive(Clone, Debug)]
+pub struct Leaderboard {
+    /// The number of games played.
+    pub games: usize,
+
+    /// The standings of the entrants.
+    pub standings: Vec<Standing>,
+}
+
+impl fmt::Display for Leaderboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.standings.iter()
+            .map(|standing| standing.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+
+        writeln!(f, "{} games", self.games)?;
+        writeln!(f, "Rank  {:<width$}  Rating  95% interval  Games  Wins", "Name", width = width)?;
+        for (rank, standing) in self.standings.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<width$}  {:>6.0}  {:>5.0} - {:<5.0}  {:>5}  {:.3}",
+                rank + 1,
+                standing.name,
+                standing.rating,
+                standing.low,
+                standing.high,
+                standing.games,
+                standing.wins,
+                width = width,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Holds the result of one game: the entrant in each seat, its place, and
+/// its share of the win.
+///
+/// A higher place means the player lasted longer; the players left standing
+/// at the end share the highest place.
+struct Game {
+    table: Vec<usize>,
+    places: Vec<usize>,
+    shares: Vec<f64>,
+}
+
+/// Holds the entrants of a tournament.
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    config: TournamentConfig,
+    entrants: Vec<(String, Entrant)>,
+    rng: StdRng,
+}
+
+/// Implements the running of a tournament.
+impl Tournament {
+    /// Constructs a tournament with no entrants.
+    pub fn new(config: TournamentConfig) -> Self {
+        Self::from_rng(config, StdRng::from_entropy())
+    }
+
+    /// Constructs a tournament with no entrants whose games are derived from
+    /// the given seed.
+    pub fn with_seed(config: TournamentConfig, seed: u64) -> Self {
+        Self::from_rng(config, StdRng::seed_from_u64(seed))
+    }
+
+    /// Constructs a tournament with no entrants from the given random number
+    /// generator.
+    fn from_rng(config: TournamentConfig, rng: StdRng) -> Self {
+        Self {
+            config,
+            entrants: Vec::new(),
+            rng,
+        }
+    }
+
+    /// Adds an entrant under the given name.
+    pub fn enter(&mut self, name: &str, entrant: Entrant) {
+        self.entrants.push((name.to_string(), entrant));
+    }
+
+    /// Gets the settings of this tournament.
+    pub fn get_config(&self) -> TournamentConfig {
+        self.config
+    }
+
+    /// Gets the names of the entrants, in the order they entered.
+    pub fn get_names(&self) -> Vec<&str> {
+        self.entrants.iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Lists the tables of the tournament, each once per seat rotation and
+    /// round.
+    ///
+    /// Every group of entrants that fits at a table gets one.  With fewer
+    /// entrants than seats, the entrants are seated in turn until the table
+    /// is full.
+    pub fn schedule(&self) -> Vec<Vec<usize>> {
+        let players = self.config.players.max(2);
+        if self.entrants.is_empty() {
+            return Vec::new();
+        }
+
+        let mut groups = Vec::new();
+        combinations(self.entrants.len(), players.min(self.entrants.len()), &mut Vec::new(), &mut groups);
+
+        let mut tables = Vec::new();
+        for _ in 0..self.config.rounds {
+            for group in &groups {
+                let table = (0..players)
+                    .map(|seat| group[seat % group.len()])
+                    .collect::<Vec<usize>>();
+
+                for rotation in 0..players {
+                    let mut rotated = table.clone();
+                    rotated.rotate_left(rotation);
+                    tables.push(rotated);
+                }
+            }
+        }
+
+        tables
+    }
+
+    /// Plays every game of the tournament and rates the entrants.
+    ///
+    /// Stops with the error if a player makes an illegal decision.
+    pub fn play(&mut self) -> Result<Leaderboard, EngineError> {
+        let tables = self.schedule()
+            .into_iter()
+            .map(|table| (table, self.rng.gen::<u64>()))
+            .collect::<Vec<(Vec<usize>, u64)>>();
+
+        let results = Batch::with_threads(self.config.threads)
+            .run(tables.len(), |i| self.game(&tables[i].0, tables[i].1));
+        let games = results.into_iter().collect::<Result<Vec<Game>, EngineError>>()?;
+
+        let entrants = self.entrants.len();
+        let ratings = fit(entrants, games.iter());
+
+        let mut resampled = vec![Vec::new(); entrants];
+        for _ in 0..self.config.resamples {
+            let sample = (0..games.len()).map(|_| &games[self.rng.gen_range(0..games.len())]);
+            for (i, rating) in fit(entrants, sample).into_iter().enumerate() {
+                resampled[i].push(rating);
+            }
+        }
+
+        let mut standings = self.entrants.iter()
+            .enumerate()
+            .map(|(i, (name, _))| {
+                let played = games.iter()
+                    .filter(|game| game.table.contains(&i))
+                    .collect::<Vec<&Game>>();
+                let wins = played.iter()
+                    .map(|game| (0..game.table.len())
+                        .filter(|&seat| game.table[seat] == i)
+                        .map(|seat| game.shares[seat])
+                        .sum::<f64>())
+                    .sum::<f64>();
+
+                Standing {
+                    name: name.clone(),
+                    rating: ratings[i],
+                    low: percentile(&mut resampled[i], 0.025).unwrap_or(ratings[i]),
+                    high: percentile(&mut resampled[i], 0.975).unwrap_or(ratings[i]),
+                    games: played.len(),
+                    wins: if played.is_empty() { 0.0 } else { wins/played.len() as f64 },
+                }
+            })
+            .collect::<Vec<Standing>>();
+        standings.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+
+        Ok(Leaderboard {
+            games: games.len(),
+            standings,
+        })
+    }
+
+    /// Plays one game between the entrants at the given table.
+    fn game(&self, table: &[usize], seed: u64) -> Result<Game, EngineError> {
+        let players = table.iter()
+            .enumerate()
+            .map(|(seat, &i)| self.entrants[i].1.player(seat, table.len() - 1))
+            .collect();
+        let mut engine = Engine::with_seed(players, self.config.rules, seed)?;
+
+        let mut winner = None;
+        for _ in 0..self.config.rules.max_turns {
+            winner = engine.turn(false)?;
+            if winner.is_some() {
+                break;
+            }
+        }
+
+        let mut places = vec![0; table.len()];
+        let mut eliminated = 0;
+        for event in engine.get_events() {
+            if let GameEvent::Eliminated { player } = event {
+                places[*player] = eliminated;
+                eliminated += 1;
+            }
+        }
+
+        let left = (0..table.len()).filter(|&seat| !engine.is_eliminated(seat)).count();
+        for (seat, place) in places.iter_mut().enumerate() {
+            if !engine.is_eliminated(seat) {
+                *place = eliminated;
+            }
+        }
+
+        let shares = (0..table.len())
+            .map(|seat| match winner {
+                Some (winner) => if seat == winner { 1.0 } else { 0.0 },
+                None => if engine.is_eliminated(seat) { 0.0 } else { 1.0/left as f64 },
+            })
+            .collect();
+
+        Ok(Game {
+            table: table.to_vec(),
+            places,
+            shares,
+        })
+    }
+}
+
+/// Lists every way to pick `size` of the numbers below `n`, in order.
+fn combinations(n: usize, size: usize, picked: &mut Vec<usize>, groups: &mut Vec<Vec<usize>>) {
+    if picked.len() == size {
+        groups.push(picked.clone());
+        return;
+    }
+
+    let start = picked.last().map_or(0, |last| last + 1);
+    for i in start..n {
+        picked.push(i);
+        combinations(n, size, picked, groups);
+        picked.pop();
+    }
+}
+
+/// Fits Bradley-Terry ratings on the Elo scale to the head-to-head results
+/// of the given games.
+///
+/// Each pair of seats held by different entrants is one result, worth 1 to
+/// the player that placed higher, or half to each if they placed the same.
+/// Every entrant also gets one drawn result against a reference player
+/// rated `BASE`, so that the ratings stay finite and entrants with few
+/// results are pulled towards the middle.
+fn fit<'a, I: Iterator<Item = &'a Game>>(entrants: usize, games: I) -> Vec<f64> {
+    let mut meetings = vec![vec![0.0f64; entrants]; entrants];
+    let mut scores = vec![0.5f64; entrants];
+
+    for game in games {
+        for a in 0..game.table.len() {
+            for b in 0..game.table.len() {
+                let (i, j) = (game.table[a], game.table[b]);
+                if i == j {
+                    continue;
+                }
+
+                meetings[i][j] += 1.0;
+                if game.places[a] > game.places[b] {
+                    scores[i] += 1.0;
+                } else if game.places[a] == game.places[b] {
+                    scores[i] += 0.5;
+                }
+            }
+        }
+    }
+
+    // Hunter's minorization-maximization updates, in terms of each
+    // entrant's strength relative to the reference player
+    let mut strengths = vec![1.0f64; entrants];
+    for _ in 0..ITERATIONS {
+        let mut change: f64 = 0.0;
+        for i in 0..entrants {
+            let mut denominator = 1.0/(strengths[i] + 1.0);
+            for j in 0..entrants {
+                if meetings[i][j] > 0.0 {
+                    denominator += meetings[i][j]/(strengths[i] + strengths[j]);
+                }
+            }
+
+            let strength = scores[i]/denominator;
+            change = change.max((strength.ln() - strengths[i].ln()).abs());
+            strengths[i] = strength;
+        }
+
+        if change < 1e-9 {
+            break;
+        }
+    }
+
+    strengths.iter()
+        .map(|strength| BASE + SCALE*strength.log10())
+        .collect()
+}
+
+/// Gets the value below which the given fraction of the values lie, or
+/// `None` if there are no values.
+fn percentile(values: &mut [f64], fraction: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_by(|a, b| a.total_cmp(b));
+    let i = ((values.len() - 1) as f64*fraction).round() as usize;
+    Some (values[i])
+}
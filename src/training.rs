@@ -0,0 +1,536 @@
+//! Implements a genetic algorithm that trains the parameters of `Agent`s.
+//!
+//! A genome is the `PlayerMetadata::Computer` of an agent.  Each generation,
+//! every agent in the population plays a number of games at random tables,
+//! and its fitness is the share of those games it won.  The next generation
+//! keeps the fittest agents as they are and fills the rest with children
+//! of parents picked by tournament selection, bred by uniform crossover and
+//! then mutated by zero-mean noise.
+
+use std::fmt;
+
+use rand::{
+    Rng,
+    SeedableRng,
+    rngs::StdRng,
+    seq::SliceRandom,
+};
+
+use crate::{
+    Agent,
+    Batch,
+    Checkpoint,
+    ActionUtilities,
+    InfluenceWeights,
+    TargetWeights,
+    BlockWeights,
+    FeatureWeights,
+    UtilityModel,
+    Engine,
+    EngineError,
+    Player,
+    PlayerMetadata,
+    RuleSet,
+};
+
+/// The number of genes that are cutoff probabilities, which come first.
+const CUTOFFS: usize = 2;
+
+/// The number of genes that are action utilities, which come next.
+const UTILITIES: usize = 10;
+
+/// The index of the temperature, which comes after the influence and
+/// target weights.
+const TEMPERATURE: usize = 19;
+
+/// The index of the first gene of the utility model, which comes next.
+const MODEL: usize = TEMPERATURE + 1;
+
+/// The number of features each action of the utility model weighs.
+const FEATURES: usize = 6;
+
+/// The index of the first block weight, which come last.
+const BLOCKING: usize = MODEL + UTILITIES*FEATURES;
+
+/// Holds the settings of a training run.
+#[derive(Clone, Copy, Debug)]
+pub struct TrainingConfig {
+    /// The number of agents in each generation.
+    pub population: usize,
+
+    /// The number of players at each table.
+    pub players: usize,
+
+    /// The number of games each agent plays per generation.
+    pub games: usize,
+
+    /// The number of fittest agents carried into the next generation
+    /// unchanged.
+    pub elites: usize,
+
+    /// The number of agents that compete to be picked as each parent.
+    /// Larger tournaments mean stronger selection pressure.
+    pub tournament: usize,
+
+    /// The probability that a child is bred from two parents rather than
+    /// copied from one.
+    pub crossover: f64,
+
+    /// The largest amount by which mutation moves a cutoff, a weight, or
+    /// the temperature.  Utilities and the weights of the utility model,
+    /// which span a range ten times as wide, move ten times as far.
+    pub mutation: f64,
+
+    /// The rules the games are played by.
+    pub rules: RuleSet,
+
+    /// The number of threads the games of a generation are spread across.
+    /// Training gives the same results whatever the number of threads.
+    pub threads: usize,
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        Self {
+            population: 64,
+            players: 4,
+            games: 20,
+            elites: 4,
+            tournament: 3,
+            crossover: 0.7,
+            mutation: 0.02,
+            rules: RuleSet::default(),
+            threads: Batch::new().get_threads(),
+        }
+    }
+}
+
+/// Summarizes how one generation fared.
+#[derive(Clone, Copy, Debug)]
+pub struct GenerationReport {
+    /// The number of the generation, counting from 0.
+    pub generation: usize,
+
+    /// The fitness of the fittest agent.
+    pub best: f64,
+
+    /// The average fitness of the population.
+    pub mean: f64,
+
+    /// The fitness of the least fit agent.
+    pub worst: f64,
+
+    /// The average spread (standard deviation) of each gene across the
+    /// population, which shrinks as the population converges.
+    pub diversity: f64,
+
+    /// The genome of the fittest agent.
+    pub champion: PlayerMetadata,
+}
+
+impl fmt::Display for GenerationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Generation {}: best {:.3}, mean {:.3}, worst {:.3}, diversity {:.3}",
+            self.generation,
+            self.best,
+            self.mean,
+            self.worst,
+            self.diversity,
+        )
+    }
+}
+
+/// Holds a population of agents being trained.
+#[derive(Clone, Debug)]
+pub struct Population {
+    config: TrainingConfig,
+    genomes: Vec<PlayerMetadata>,
+    generation: usize,
+    rng: StdRng,
+}
+
+/// Implements the training of a population.
+impl Population {
+    /// Constructs a population of random agents.
+    pub fn new(config: TrainingConfig) -> Self {
+        Self::from_rng(config, StdRng::from_entropy())
+    }
+
+    /// Constructs a population of random agents whose training is derived
+    /// from the given seed.
+    pub fn with_seed(config: TrainingConfig, seed: u64) -> Self {
+        Self::from_rng(config, StdRng::seed_from_u64(seed))
+    }
+
+    /// Constructs a population of random agents from the given random number
+    /// generator.
+    ///
+    /// The population always has at least one agent.
+    fn from_rng(config: TrainingConfig, mut rng: StdRng) -> Self {
+        let genomes = (0..config.population.max(1))
+            .map(|_| PlayerMetadata::Computer {
+                lying_cutoff: rng.gen(),
+                liar_cutoff: rng.gen(),
+                utilities: ActionUtilities::random(&mut rng),
+                influence: InfluenceWeights::random(&mut rng),
+                targeting: TargetWeights::random(&mut rng),
+                blocking: BlockWeights::random(&mut rng),
+                model: UtilityModel::random(&mut rng),
+
+                // Start deterministic, and let mutation raise the temperature
+                temperature: 0.0,
+            })
+            .collect();
+
+        Self {
+            config,
+            genomes,
+            generation: 0,
+            rng,
+        }
+    }
+
+    /// Constructs a population from a checkpoint, to continue training
+    /// where it left off.
+    ///
+    /// Checkpoints do not hold the state of the random number generator, so
+    /// the rest of the training is derived from the given seed instead.  If
+    /// the checkpoint holds no genomes, the population starts out random.
+    pub fn resume(config: TrainingConfig, checkpoint: Checkpoint, seed: u64) -> Self {
+        let mut population = Self::with_seed(config, seed);
+        if !checkpoint.genomes.is_empty() {
+            population.genomes = checkpoint.genomes;
+            population.generation = checkpoint.generation;
+        }
+
+        population
+    }
+
+    /// Gets a checkpoint of the current generation.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            generation: self.generation,
+            genomes: self.genomes.clone(),
+        }
+    }
+
+    /// Gets the settings of this training run.
+    pub fn get_config(&self) -> TrainingConfig {
+        self.config
+    }
+
+    /// Gets the genomes of the current generation.
+    pub fn get_genomes(&self) -> &[PlayerMetadata] {
+        &self.genomes
+    }
+
+    /// Gets the number of the current generation, counting from 0.
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Plays the given number of generations, and gets the report of each.
+    ///
+    /// Returns an error if the rules cannot be used with the configured
+    /// number of players, or if an agent makes an illegal decision.
+    pub fn evolve(&mut self, generations: usize) -> Result<Vec<GenerationReport>, EngineError> {
+        (0..generations).map(|_| self.step()).collect()
+    }
+
+    /// Plays one generation: measures the fitness of every agent, reports
+    /// on it, and replaces the population with the next generation.
+    ///
+    /// Returns an error if the rules cannot be used with the configured
+    /// number of players, or if an agent makes an illegal decision.
+    pub fn step(&mut self) -> Result<GenerationReport, EngineError> {
+        let fitness = self.evaluate()?;
+
+        // Rank the agents from the fittest down
+        // Note: it's OK to use `Option::unwrap` here because fitness is
+        // always a valid share of games
+        let mut ranking = (0..self.genomes.len()).collect::<Vec<usize>>();
+        ranking.sort_by(|&a, &b| fitness[b].partial_cmp(&fitness[a]).unwrap());
+
+        let report = GenerationReport {
+            generation: self.generation,
+            best: fitness[ranking[0]],
+            mean: fitness.iter().sum::<f64>()/fitness.len() as f64,
+            worst: fitness[ranking[ranking.len() - 1]],
+            diversity: self.diversity(),
+            champion: self.genomes[ranking[0]],
+        };
+
+        let mut next = ranking.iter()
+            .take(self.config.elites)
+            .map(|&i| self.genomes[i])
+            .collect::<Vec<PlayerMetadata>>();
+
+        while next.len() < self.genomes.len() {
+            let mother = self.select(&fitness);
+            let mother = genes(&self.genomes[mother]);
+            let child = if self.rng.gen_bool(self.config.crossover.clamp(0.0, 1.0)) {
+                let father = self.select(&fitness);
+                let father = genes(&self.genomes[father]);
+                self.crossover(&mother, &father)
+            } else {
+                mother
+            };
+
+            next.push(from_genes(&self.mutate(child)));
+        }
+
+        self.genomes = next;
+        self.generation += 1;
+
+        Ok(report)
+    }
+
+    /// Plays this generation's games and gets the fitness of every agent:
+    /// the share of the games it played that it won.
+    ///
+    /// Each round, the agents are shuffled and seated at tables.  If they do
+    /// not fill the last table, agents picked at random play an extra game.
+    /// Every table is seated and seeded before any game is played, and the
+    /// games are then spread across `TrainingConfig::threads`.
+    fn evaluate(&mut self) -> Result<Vec<f64>, EngineError> {
+        let players = self.config.players.max(1);
+        let mut tables = Vec::new();
+
+        for _ in 0..self.config.games {
+            let mut order = (0..self.genomes.len()).collect::<Vec<usize>>();
+            order.shuffle(&mut self.rng);
+            while order.len() % players != 0 {
+                order.push(self.rng.gen_range(0..self.genomes.len()));
+            }
+
+            for table in order.chunks(players) {
+                tables.push((table.to_vec(), self.rng.gen::<u64>()));
+            }
+        }
+
+        let results = Batch::with_threads(self.config.threads)
+            .run(tables.len(), |i| self.play(&tables[i].0, tables[i].1));
+
+        let mut points = vec![0.0; self.genomes.len()];
+        let mut played = vec![0usize; self.genomes.len()];
+        for ((table, _), shares) in tables.iter().zip(results) {
+            for (seat, share) in shares?.into_iter().enumerate() {
+                points[table[seat]] += share;
+                played[table[seat]] += 1;
+            }
+        }
+
+        Ok(points.iter()
+            .zip(played.iter())
+            .map(|(points, played)| if *played == 0 { 0.0 } else { points/(*played as f64) })
+            .collect())
+    }
+
+    /// Plays one game between the given agents, and gets each seat's share
+    /// of the win: 1 for the winner, or an equal share among the players
+    /// left standing if the game reaches `RuleSet::max_turns`.
+    fn play(&self, table: &[usize], seed: u64) -> Result<Vec<f64>, EngineError> {
+        let players = table.iter()
+            .enumerate()
+            .map(|(seat, &i)| Box::new(Agent::from_metadata(seat, table.len() - 1, self.genomes[i])) as Box<dyn Player>)
+            .collect();
+        let mut engine = Engine::with_seed(players, self.config.rules, seed)?;
+
+        for _ in 0..self.config.rules.max_turns {
+            if let Some (winner) = engine.turn(false)? {
+                return Ok((0..table.len()).map(|seat| if seat == winner { 1.0 } else { 0.0 }).collect());
+            }
+        }
+
+        let left = (0..table.len()).filter(|&seat| !engine.is_eliminated(seat)).count();
+        Ok((0..table.len())
+            .map(|seat| if engine.is_eliminated(seat) { 0.0 } else { 1.0/left as f64 })
+            .collect())
+    }
+
+    /// Picks a parent: the fittest of a few agents drawn at random.
+    fn select(&mut self, fitness: &[f64]) -> usize {
+        let mut best = self.rng.gen_range(0..self.genomes.len());
+        for _ in 1..self.config.tournament {
+            let other = self.rng.gen_range(0..self.genomes.len());
+            if fitness[other] > fitness[best] {
+                best = other;
+            }
+        }
+
+        best
+    }
+
+    /// Breeds a child that takes each gene from either parent at random.
+    fn crossover(&mut self, mother: &[f64], father: &[f64]) -> Vec<f64> {
+        mother.iter()
+            .zip(father.iter())
+            .map(|(m, f)| if self.rng.gen() { *m } else { *f })
+            .collect()
+    }
+
+    /// Moves every gene by zero-mean noise.  Cutoffs stay probabilities, and
+    /// the temperature stays non-negative.
+    fn mutate(&mut self, mut genes: Vec<f64>) -> Vec<f64> {
+        for (i, gene) in genes.iter_mut().enumerate() {
+            let amount = if (CUTOFFS..CUTOFFS + UTILITIES).contains(&i) || (MODEL..BLOCKING).contains(&i) {
+                10.0*self.config.mutation
+            } else {
+                self.config.mutation
+            };
+
+            *gene += amount*(2.0*self.rng.gen::<f64>() - 1.0);
+            if i < CUTOFFS {
+                *gene = gene.clamp(0.0, 1.0);
+            } else if i == TEMPERATURE {
+                *gene = gene.max(0.0);
+            }
+        }
+
+        genes
+    }
+
+    /// Gets the average standard deviation of each gene across the
+    /// population.
+    fn diversity(&self) -> f64 {
+        let genomes = self.genomes.iter().map(genes).collect::<Vec<Vec<f64>>>();
+        let count = genomes.len() as f64;
+        let length = genomes.first().map_or(0, |genes| genes.len());
+
+        (0..length)
+            .map(|i| {
+                let mean = genomes.iter().map(|genes| genes[i]).sum::<f64>()/count;
+                let variance = genomes.iter().map(|genes| (genes[i] - mean).powi(2)).sum::<f64>()/count;
+                variance.sqrt()
+            })
+            .sum::<f64>()/length.max(1) as f64
+    }
+}
+
+/// Lists the genes of a genome: the cutoffs, then the utilities, then the
+/// influence weights, then the target weights, then the temperature, then
+/// the weights of the utility model, action by action, and last the block
+/// weights.
+///
+/// Genomes of players other than agents have no genes.
+fn genes(genome: &PlayerMetadata) -> Vec<f64> {
+    match genome {
+        PlayerMetadata::Computer { lying_cutoff, liar_cutoff, utilities, influence, targeting, blocking, model, temperature } => [vec![
+            *lying_cutoff,
+            *liar_cutoff,
+            utilities.income,
+            utilities.foreignaid,
+            utilities.coup,
+            utilities.tax,
+            utilities.assassinate,
+            utilities.exchange,
+            utilities.steal,
+            utilities.convert,
+            utilities.embezzle,
+            utilities.examine,
+            influence.action,
+            influence.defense,
+            influence.claim,
+            targeting.coins,
+            targeting.influence,
+            targeting.threat,
+            targeting.blockers,
+            *temperature,
+        ], model_genes(model), vec![
+            blocking.aid,
+            blocking.suspicion,
+            blocking.consistency,
+        ]].concat(),
+        _ => Vec::new(),
+    }
+}
+
+/// Builds a genome from the genes listed by `genes`.
+fn from_genes(genes: &[f64]) -> PlayerMetadata {
+    let gene = |i: usize| genes.get(i).copied().unwrap_or(0.0);
+    let weights = |action: usize| {
+        let start = MODEL + FEATURES*action;
+        FeatureWeights {
+            coins: gene(start),
+            influence: gene(start + 1),
+            opponent_coins: gene(start + 2),
+            opponent_influence: gene(start + 3),
+            honest: gene(start + 4),
+            blocked: gene(start + 5),
+        }
+    };
+
+    PlayerMetadata::Computer {
+        lying_cutoff: gene(0),
+        liar_cutoff: gene(1),
+        utilities: ActionUtilities {
+            income: gene(2),
+            foreignaid: gene(3),
+            coup: gene(4),
+            tax: gene(5),
+            assassinate: gene(6),
+            exchange: gene(7),
+            steal: gene(8),
+            convert: gene(9),
+            embezzle: gene(10),
+            examine: gene(11),
+        },
+        influence: InfluenceWeights {
+            action: gene(12),
+            defense: gene(13),
+            claim: gene(14),
+        },
+        targeting: TargetWeights {
+            coins: gene(15),
+            influence: gene(16),
+            threat: gene(17),
+            blockers: gene(18),
+        },
+        blocking: BlockWeights {
+            aid: gene(BLOCKING),
+            suspicion: gene(BLOCKING + 1),
+            consistency: gene(BLOCKING + 2),
+        },
+        temperature: gene(TEMPERATURE),
+        model: UtilityModel {
+            income: weights(0),
+            foreignaid: weights(1),
+            coup: weights(2),
+            tax: weights(3),
+            assassinate: weights(4),
+            exchange: weights(5),
+            steal: weights(6),
+            convert: weights(7),
+            embezzle: weights(8),
+            examine: weights(9),
+        },
+    }
+}
+
+/// Lists the genes of a utility model, in the order of `UtilityModel`'s
+/// actions and then of `FeatureWeights`' features.
+fn model_genes(model: &UtilityModel) -> Vec<f64> {
+    [
+        model.income,
+        model.foreignaid,
+        model.coup,
+        model.tax,
+        model.assassinate,
+        model.exchange,
+        model.steal,
+        model.convert,
+        model.embezzle,
+        model.examine,
+    ].iter()
+        .flat_map(|weights| [
+            weights.coins,
+            weights.influence,
+            weights.opponent_coins,
+            weights.opponent_influence,
+            weights.honest,
+            weights.blocked,
+        ])
+        .collect()
+}
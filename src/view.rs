@@ -0,0 +1,118 @@
+//! Implements the view of the game that the engine gives to each player.
+
+use crate::{
+    Card,
+    Action,
+    Faction,
+    GameEvent,
+    RuleSet,
+};
+
+/// Holds the public information about one player.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PlayerState {
+    /// The number of coins this player has.
+    pub coins: u8,
+
+    /// The cards this player has lost (turned face up), in order.
+    pub revealed: Vec<Card>,
+
+    /// This player's faction, or `None` if the game is played without the
+    /// Reformation expansion.
+    pub faction: Option<Faction>,
+}
+
+/// Implements commonly used functions performed on player states.
+impl PlayerState {
+    /// Gets the number of influence this player has left.
+    pub fn influence(&self) -> usize {
+        2usize.saturating_sub(self.revealed.len())
+    }
+
+    /// Checks whether or not this player is out of the game.
+    pub fn is_eliminated(&self) -> bool {
+        self.influence() == 0
+    }
+}
+
+/// Holds everything a given player is allowed to know about the game.
+///
+/// The engine builds a fresh view for a player each time it asks that player
+/// to make a decision.
+#[derive(Clone, Debug)]
+pub struct GameView {
+    /// The ID of the player this view was built for.
+    pub id: usize,
+
+    /// This player's own hand.  Lost cards are `Card::None`.
+    pub hand: [Card; 2],
+
+    /// The public state of every player, indexed by ID.
+    pub players: Vec<PlayerState>,
+
+    /// The rules of this game.
+    pub rules: RuleSet,
+
+    /// The number of coins in the Treasury Reserve (Reformation only).
+    pub reserve: u8,
+
+    /// The number of cards left in the deck.
+    pub deck_size: usize,
+
+    /// The number of the current turn, starting at 1.
+    pub turn: usize,
+
+    /// The ID of the player whose turn it is.
+    pub active_player: usize,
+
+    /// The action currently being resolved, if any.
+    pub current: Option<Action>,
+
+    /// The actions this player may legally declare (see `Engine::legal_actions`).
+    pub legal_actions: Vec<Action>,
+
+    /// Every public event that has occurred so far, in order.
+    pub history: Vec<GameEvent>,
+}
+
+/// Implements commonly used functions performed on game views.
+impl GameView {
+    /// Gets the IDs of the players who are still in the game, other than this one.
+    pub fn opponents(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|&i| i != self.id && !self.players[i].is_eliminated())
+            .collect()
+    }
+
+    /// Gets the IDs of the players who are out of the game.
+    pub fn eliminated_players(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|&i| self.players[i].is_eliminated())
+            .collect()
+    }
+
+    /// Gets every card that has been turned face up.
+    pub fn killed(&self) -> Vec<Card> {
+        self.players.iter()
+            .flat_map(|p| p.revealed.iter().copied())
+            .collect()
+    }
+
+    /// Checks whether or not two players are protected from each other by
+    /// belonging to the same faction.
+    /// 
+    /// Players of the same faction may not target or block one another,
+    /// unless every player left in the game belongs to that faction.
+    pub fn allied(&self, a: usize, b: usize) -> bool {
+        let faction = self.players[a].faction;
+
+        faction.is_some()
+            && faction == self.players[b].faction
+            && self.players.iter().any(|p| !p.is_eliminated() && p.faction != faction)
+    }
+
+    /// Gets this player's coins.
+    pub fn coins(&self) -> u8 {
+        self.players[self.id].coins
+    }
+}
@@ -0,0 +1,53 @@
+//! Checks that games run across threads give the same results as serial
+//! games.
+
+use contessa::{
+    Agent,
+    Batch,
+    Engine,
+    Player,
+    RuleSet,
+};
+
+/// Fails to compile unless `T` can be moved to another thread.
+fn assert_send<T: Send>() {}
+
+/// Builds a game of agents derived entirely from the given seed.
+fn game(seed: u64) -> Engine {
+    let players = (0..4)
+        .map(|i| Box::new(Agent::with_seed(i, 3, seed + i as u64)) as Box<dyn Player>)
+        .collect();
+
+    let rules = RuleSet {
+        max_turns: 100,
+        ..RuleSet::default()
+    };
+
+    Engine::with_seed(players, rules, seed).unwrap()
+}
+
+#[test]
+fn players_and_engines_are_send() {
+    assert_send::<Engine>();
+    assert_send::<Box<dyn Player>>();
+}
+
+#[test]
+fn results_come_back_in_order() {
+    let squares = Batch::with_threads(4).run(100, |i| i*i);
+    assert_eq!(squares, (0..100).map(|i| i*i).collect::<Vec<usize>>());
+    assert!(Batch::with_threads(4).run(0, |i| i).is_empty());
+    assert_eq!(Batch::with_threads(0).get_threads(), 1);
+}
+
+#[test]
+fn parallel_games_match_serial_games() {
+    let serial = Batch::with_threads(1).play((0..8).map(game).collect());
+    let parallel = Batch::with_threads(4).play((0..8).map(game).collect());
+
+    assert_eq!(serial.len(), 8);
+    for ((a, a_winner), (b, b_winner)) in serial.iter().zip(parallel.iter()) {
+        assert_eq!(a_winner.as_ref().ok(), b_winner.as_ref().ok());
+        assert_eq!(a.get_events(), b.get_events());
+    }
+}
@@ -0,0 +1,135 @@
+//! Checks that beliefs about other players' hands follow what they do.
+
+use contessa::{
+    Card,
+    Action,
+    Belief,
+    GameEvent,
+    GameView,
+    PlayerState,
+    RuleSet,
+};
+
+/// Builds the view of player 0 in a three-player game with the given history.
+fn view(history: Vec<GameEvent>) -> GameView {
+    let mut players = vec![
+        PlayerState {
+            coins: 2,
+            revealed: Vec::new(),
+            faction: None,
+        };
+        3
+    ];
+
+    for event in history.iter() {
+        if let GameEvent::InfluenceLost { player, card } = event {
+            players[*player].revealed.push(*card);
+        }
+    }
+
+    GameView {
+        id: 0,
+        hand: [Card::Captain, Card::Contessa],
+        players,
+        rules: RuleSet::default(),
+        reserve: 0,
+        deck_size: 9,
+        turn: 1,
+        active_player: 0,
+        current: None,
+        legal_actions: Vec::new(),
+        history,
+    }
+}
+
+/// Gets player 0's belief that a player has a card after the given history.
+fn belief(history: Vec<GameEvent>, player: usize, card: Card) -> f64 {
+    let mut belief = Belief::new();
+    belief.update(&view(history));
+    belief.probability(player, card)
+}
+
+#[test]
+fn claims_raise_belief() {
+    let prior = belief(Vec::new(), 1, Card::Duke);
+    let claimed = belief(vec![
+        GameEvent::ActionDeclared { player: 1, action: Action::Tax },
+    ], 1, Card::Duke);
+
+    assert!(prior > 0.0 && prior < 1.0);
+    assert!(claimed > prior);
+    assert_eq!(belief(Vec::new(), 2, Card::Duke), prior);
+}
+
+#[test]
+fn beliefs_carry_over_between_updates() {
+    let declared = GameEvent::ActionDeclared { player: 1, action: Action::Tax };
+
+    let mut belief = Belief::new();
+    belief.update(&view(vec![declared.clone()]));
+    let once = belief.probability(1, Card::Duke);
+
+    // The same history does not count twice
+    belief.update(&view(vec![declared.clone()]));
+    assert_eq!(belief.probability(1, Card::Duke), once);
+
+    belief.update(&view(vec![declared.clone(), declared]));
+    assert!(belief.probability(1, Card::Duke) > once);
+}
+
+#[test]
+fn unblocked_actions_lower_belief() {
+    let passed = belief(vec![
+        GameEvent::ActionDeclared { player: 1, action: Action::ForeignAid },
+        GameEvent::ActionPerformed { player: 1, action: Action::ForeignAid },
+    ], 2, Card::Duke);
+
+    assert!(passed < belief(Vec::new(), 2, Card::Duke));
+}
+
+#[test]
+fn challenges_settle_beliefs() {
+    let lost = belief(vec![
+        GameEvent::ActionDeclared { player: 1, action: Action::Tax },
+        GameEvent::Challenge { challenger: 2, claimant: 1, card: Card::Duke },
+        GameEvent::ChallengeResolved { challenger: 2, claimant: 1, card: Card::Duke, success: true },
+        GameEvent::InfluenceLost { player: 1, card: Card::Assassin },
+    ], 1, Card::Duke);
+    assert_eq!(lost, 0.0);
+
+    // A player who proves a claim replaces the card they revealed
+    let won = belief(vec![
+        GameEvent::ActionDeclared { player: 1, action: Action::Tax },
+        GameEvent::Challenge { challenger: 2, claimant: 1, card: Card::Duke },
+        GameEvent::ChallengeResolved { challenger: 2, claimant: 1, card: Card::Duke, success: false },
+        GameEvent::CardRevealed { player: 1, card: Card::Duke },
+        GameEvent::InfluenceLost { player: 2, card: Card::Assassin },
+        GameEvent::CardReplaced { player: 1, returned: Card::Duke, drawn: Card::None },
+    ], 1, Card::Duke);
+    assert!(won > 0.0 && won < 1.0);
+}
+
+#[test]
+fn exchanges_forget_claims() {
+    let exchanged = belief(vec![
+        GameEvent::ActionDeclared { player: 1, action: Action::Tax },
+        GameEvent::ActionPerformed { player: 1, action: Action::Tax },
+        GameEvent::ActionDeclared { player: 1, action: Action::Exchange },
+        GameEvent::ActionPerformed { player: 1, action: Action::Exchange },
+        GameEvent::Exchange { player: 1, drawn: vec![Card::None; 2], returned: vec![Card::None; 2] },
+    ], 1, Card::Duke);
+
+    assert_eq!(exchanged, belief(Vec::new(), 1, Card::Duke));
+}
+
+#[test]
+fn eliminated_players_hold_nothing() {
+    let history = vec![
+        GameEvent::InfluenceLost { player: 1, card: Card::Duke },
+        GameEvent::InfluenceLost { player: 1, card: Card::Assassin },
+    ];
+
+    for card in RuleSet::default().cards() {
+        assert_eq!(belief(history.clone(), 1, card), 0.0);
+    }
+}
@@ -0,0 +1,113 @@
+//! Checks that CFR training produces strategy tables that play legal games.
+
+use std::sync::Arc;
+
+use contessa::{
+    Agent,
+    CfrAgent,
+    CfrTrainer,
+    Engine,
+    Player,
+    RuleSet,
+    StrategyError,
+    StrategyTable,
+};
+
+/// Plays a game of players following the given tables, each in its own
+/// seat, and gets the winner (if the game ends within 100 turns).
+fn play(rules: RuleSet, tables: &[Arc<StrategyTable>], seed: u64) -> Option<usize> {
+    let players = tables.iter()
+        .enumerate()
+        .map(|(i, table)| Box::new(CfrAgent::with_seed(i, table.clone(), seed + i as u64)) as Box<dyn Player>)
+        .collect();
+    let mut engine = Engine::with_seed(players, rules, seed).unwrap();
+
+    for _ in 0..100 {
+        if let Some (winner) = engine.turn(false).unwrap() {
+            return Some (winner);
+        }
+    }
+
+    None
+}
+
+#[test]
+fn cfr_agents_play_legal_games_under_every_rule_set() {
+    let variants = [
+        (RuleSet::default(), 4),
+        (RuleSet { reformation: true, ..RuleSet::default() }, 4),
+        (RuleSet { inquisitor: true, ..RuleSet::default() }, 3),
+        (RuleSet::for_players(2), 2),
+    ];
+
+    for (rules, players) in variants {
+        let mut trainer = CfrTrainer::with_seed(rules, players, 5);
+        trainer.train(50).unwrap();
+        let table = Arc::new(trainer.strategy());
+
+        let mut list: Vec<Box<dyn Player>> = vec![Box::new(Agent::with_seed(0, players - 1, 5))];
+        for i in 1..players {
+            list.push(Box::new(CfrAgent::with_seed(i, table.clone(), i as u64)));
+        }
+
+        let mut engine = Engine::with_seed(list, rules, 5).unwrap();
+        assert!(engine.play(false).is_ok());
+    }
+}
+
+#[test]
+fn training_is_reproducible_and_strategies_are_distributions() {
+    let mut first = CfrTrainer::with_seed(RuleSet::default(), 3, 9);
+    let mut second = CfrTrainer::with_seed(RuleSet::default(), 3, 9);
+    first.train(100).unwrap();
+    second.train(100).unwrap();
+
+    let strategy = first.strategy();
+    assert_eq!(strategy, second.strategy());
+    assert!(!strategy.is_empty());
+
+    for (_, options) in strategy.iter() {
+        let total = options.iter().map(|(_, p)| p).sum::<f64>();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(options.iter().all(|(_, p)| *p >= 0.0));
+    }
+}
+
+#[test]
+fn strategy_tables_round_trip() {
+    let mut trainer = CfrTrainer::with_seed(RuleSet::for_players(2), 2, 1);
+    trainer.train(100).unwrap();
+    let strategy = trainer.strategy();
+
+    let parsed = strategy.to_string().parse::<StrategyTable>().unwrap();
+    assert_eq!(parsed, strategy);
+
+    let error = "strategy 1\nset act/Duke/c0 Income=1\nset broken".parse::<StrategyTable>();
+    assert!(matches!(error, Err(StrategyError::Parse { line: 3, .. })));
+}
+
+#[test]
+fn trained_strategy_beats_uniform_play() {
+    let rules = RuleSet::for_players(2);
+    let mut trainer = CfrTrainer::with_seed(rules, 2, 3);
+    trainer.train(5000).unwrap();
+
+    let trained = Arc::new(trainer.strategy());
+    let uniform = Arc::new(StrategyTable::new());
+
+    // Alternate seats so that neither player always moves first
+    let mut wins = 0;
+    for game in 0..200 {
+        let seat = game % 2;
+        let mut tables = vec![uniform.clone(), uniform.clone()];
+        tables[seat] = trained.clone();
+        if play(rules, &tables, game as u64) == Some (seat) {
+            wins += 1;
+        }
+    }
+
+    assert!(wins > 120, "the trained strategy won {} of 200 games", wins);
+
+    let exploitability = trainer.exploitability(100, 50).unwrap();
+    assert!(exploitability.is_finite() && exploitability.abs() <= 1.0);
+}
@@ -0,0 +1,75 @@
+//! Builds the computer genomes shared by the tests.
+
+use contessa::{
+    Agent,
+    ActionUtilities,
+    InfluenceWeights,
+    TargetWeights,
+    BlockWeights,
+    UtilityModel,
+    PlayerMetadata,
+};
+
+/// Holds the fields of a `PlayerMetadata::Computer` genome, so that a test
+/// can override the few it cares about and take the rest from the default.
+///
+/// The default genome values every action and every reason to keep a card
+/// at 1, has both cutoffs at 0, neutral target and block weights, and a
+/// temperature of 0.
+#[derive(Clone, Copy, Debug)]
+pub struct Genome {
+    pub lying_cutoff: f64,
+    pub liar_cutoff: f64,
+    pub utilities: ActionUtilities,
+    pub influence: InfluenceWeights,
+    pub targeting: TargetWeights,
+    pub blocking: BlockWeights,
+    pub model: UtilityModel,
+    pub temperature: f64,
+}
+
+impl Default for Genome {
+    fn default() -> Self {
+        Self {
+            lying_cutoff: 0.0,
+            liar_cutoff: 0.0,
+            utilities: ActionUtilities {
+                income: 1.0,
+                foreignaid: 1.0,
+                coup: 1.0,
+                tax: 1.0,
+                assassinate: 1.0,
+                exchange: 1.0,
+                steal: 1.0,
+                convert: 1.0,
+                embezzle: 1.0,
+                examine: 1.0,
+            },
+            influence: InfluenceWeights {
+                action: 1.0,
+                defense: 1.0,
+                claim: 1.0,
+            },
+            targeting: TargetWeights::default(),
+            blocking: BlockWeights::default(),
+            model: UtilityModel::default(),
+            temperature: 0.0,
+        }
+    }
+}
+
+impl Genome {
+    /// Builds an agent with this genome.
+    pub fn agent(self, id: usize, opponents: usize) -> Agent {
+        Agent::from_metadata(id, opponents, PlayerMetadata::Computer {
+            lying_cutoff: self.lying_cutoff,
+            liar_cutoff: self.liar_cutoff,
+            utilities: self.utilities,
+            influence: self.influence,
+            targeting: self.targeting,
+            blocking: self.blocking,
+            model: self.model,
+            temperature: self.temperature,
+        })
+    }
+}
@@ -0,0 +1,75 @@
+//! Checks the exact odds of hidden hands against hand-counted cases.
+
+use contessa::{
+    Card,
+    Distribution,
+    PerceivedHand,
+    RuleSet,
+};
+
+/// Checks that two probabilities agree up to rounding.
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+#[test]
+fn hands_are_hypergeometric() {
+    let rules = RuleSet::default();
+    let odds = Distribution::new(&rules, &[Card::Duke, Card::Captain], vec![0, 2, 2]);
+
+    // 13 cards are unseen, 2 of them Dukes
+    assert_eq!(odds.unseen(), 13);
+    assert_eq!(odds.count(Card::Duke), 2);
+
+    let hands = odds.hands(1);
+    assert_eq!(hands.len(), 15);
+    assert!(close(hands.iter().map(|(_, p)| p).sum(), 1.0));
+
+    assert!(close(odds.copies(1, Card::Duke, 2), 1.0/78.0));
+    assert!(close(odds.copies(1, Card::Duke, 1), 2.0*11.0/78.0));
+    assert!(close(odds.probability(1, Card::Duke), 23.0/78.0));
+    assert!(close(odds.copies(1, Card::Contessa, 2), 3.0/78.0));
+    assert_eq!(odds.hands(0), vec![(Vec::new(), 1.0)]);
+}
+
+#[test]
+fn dead_cards_and_lost_influence_count() {
+    let rules = RuleSet::default();
+    let odds = Distribution::new(&rules, &[Card::Duke; 3], vec![1, 1]);
+
+    assert_eq!(odds.probability(0, Card::Duke), 0.0);
+    assert!(close(odds.probability(0, Card::Captain), 3.0/12.0));
+    assert_eq!(odds.copies(0, Card::Captain, 2), 0.0);
+    assert!(odds.hands(0).iter().all(|(hand, _)| hand.len() == 1));
+}
+
+#[test]
+fn joint_hands_share_the_unseen_cards() {
+    let rules = RuleSet {
+        copies: 1,
+        ..RuleSet::default()
+    };
+    let odds = Distribution::new(&rules, &[Card::Duke, Card::Captain], vec![1, 1, 1]);
+
+    // Three players each hold one of the three unseen cards
+    let joint = odds.joint(&[0, 1, 2]);
+    assert_eq!(joint.len(), 6);
+    assert!(joint.iter().all(|(_, p)| close(*p, 1.0/6.0)));
+    assert!(joint.iter().all(|(hands, _)| hands[0] != hands[1] && hands[1] != hands[2] && hands[0] != hands[2]));
+}
+
+#[test]
+fn perceived_hands_keep_pairs() {
+    let rules = RuleSet::default();
+    let odds = Distribution::new(&rules, &[], vec![2]);
+    let perceived = PerceivedHand::from_hands(&rules.cards(), &odds.hands(0));
+
+    assert!(close(*perceived.get(&Card::Duke).unwrap(), 1.0 - 12.0*11.0/(15.0*14.0)));
+    assert!(close(*perceived.get_pair(&Card::Duke).unwrap(), 3.0/105.0));
+
+    let mut known = PerceivedHand::new();
+    known.assign(&[Card::Contessa, Card::Contessa]);
+    assert_eq!(known.get(&Card::Contessa), Some (&1.0));
+    assert_eq!(known.get_pair(&Card::Contessa), Some (&1.0));
+    assert_eq!(known.get(&Card::Duke), Some (&0.0));
+}
@@ -0,0 +1,121 @@
+//! Checks that genomes and checkpoints survive a trip through a file.
+
+use std::{
+    env,
+    fs,
+    path::PathBuf,
+};
+
+use contessa::{
+    Agent,
+    Checkpoint,
+    GenomeError,
+    Player,
+    PlayerMetadata,
+    Population,
+    TargetWeights,
+    BlockWeights,
+    UtilityModel,
+    TrainingConfig,
+};
+
+/// A file in the temporary directory, unique to this test binary.
+fn temp(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("contessa-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn genome_round_trips() {
+    let genome = Agent::with_seed(1, 3, 3).get_metadata();
+    let path = temp("genome.json");
+
+    genome.save(&path).unwrap();
+    let loaded = PlayerMetadata::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, genome);
+}
+
+#[test]
+fn unknown_versions_are_rejected() {
+    let genome = Agent::with_seed(1, 3, 5).get_metadata();
+    let path = temp("version.json");
+
+    genome.save(&path).unwrap();
+    let text = fs::read_to_string(&path).unwrap().replacen("\"version\": 1", "\"version\": 99", 1);
+    fs::write(&path, text).unwrap();
+    let loaded = PlayerMetadata::load(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert!(matches!(loaded, Err(GenomeError::Version (99))));
+}
+
+#[test]
+fn genomes_saved_before_newer_weights_still_load() {
+    // A genome as saved before agents had target weights, block weights, a
+    // utility model, or a temperature
+    let text = r#"{
+  "version": 1,
+  "contents": {
+    "Computer": {
+      "lying_cutoff": 0.25,
+      "liar_cutoff": 0.5,
+      "utilities": {
+        "income": 1.0,
+        "foreignaid": 2.0,
+        "coup": 3.0,
+        "tax": 4.0,
+        "assassinate": 5.0,
+        "exchange": 6.0,
+        "steal": 7.0,
+        "convert": 8.0,
+        "embezzle": 9.0,
+        "examine": 10.0
+      },
+      "influence": {
+        "action": 1.0,
+        "defense": 0.5,
+        "claim": 0.25
+      }
+    }
+  }
+}"#;
+    let path = temp("old.json");
+
+    fs::write(&path, text).unwrap();
+    let loaded = PlayerMetadata::load(&path);
+    fs::remove_file(&path).unwrap();
+
+    match loaded.unwrap() {
+        PlayerMetadata::Computer { lying_cutoff, utilities, targeting, blocking, model, temperature, .. } => {
+            assert_eq!(lying_cutoff, 0.25);
+            assert_eq!(utilities.examine, 10.0);
+            assert_eq!(targeting, TargetWeights::default());
+            assert_eq!(blocking, BlockWeights::default());
+            assert_eq!(model, UtilityModel::default());
+            assert_eq!(temperature, 0.0);
+        },
+        other => panic!("expected a computer genome, got {:?}", other),
+    }
+}
+
+#[test]
+fn training_resumes_from_a_checkpoint() {
+    let config = TrainingConfig {
+        population: 6,
+        games: 2,
+        elites: 2,
+        ..TrainingConfig::default()
+    };
+    let mut population = Population::with_seed(config, 8);
+    population.evolve(1).unwrap();
+    let path = temp("checkpoint.json");
+
+    population.checkpoint().save(&path).unwrap();
+    let checkpoint = Checkpoint::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let resumed = Population::resume(config, checkpoint, 1);
+    assert_eq!(resumed.get_generation(), 1);
+    assert_eq!(resumed.get_genomes(), population.get_genomes());
+}
@@ -0,0 +1,1141 @@
+//! Checks that the engine follows the official rules of Coup.
+
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use contessa::{
+    Card,
+    Action,
+    Agent,
+    ActionUtilities,
+    TargetWeights,
+    BlockWeights,
+    FeatureWeights,
+    UtilityModel,
+    Engine,
+    EngineError,
+    Faction,
+    GameEvent,
+    GameRecord,
+    GameView,
+    Player,
+    PlayerMetadata,
+    RuleSet,
+};
+
+mod common;
+
+use common::Genome;
+
+/// Records which players were asked to challenge or block.
+type Log = Arc<Mutex<Vec<(usize, &'static str)>>>;
+
+/// A player whose decisions are fixed in advance.
+#[derive(Clone)]
+struct Scripted {
+    id: usize,
+    hand: [Card; 2],
+    coins: u8,
+    actions: Vec<Action>,
+    challenge: bool,
+    block: Option<Card>,
+    lose: Option<Card>,
+    swap: bool,
+    log: Log,
+}
+
+impl Scripted {
+    fn new(id: usize, hand: [Card; 2], log: &Log) -> Self {
+        Self {
+            id,
+            hand,
+            coins: 0,
+            actions: Vec::new(),
+            challenge: false,
+            block: None,
+            lose: None,
+            swap: false,
+            log: log.clone(),
+        }
+    }
+
+    /// Sets the coins this player has once the starting coins are handed out.
+    fn coins(mut self, coins: u8) -> Self {
+        self.coins = coins - RuleSet::default().starting_coins;
+        self
+    }
+
+    fn acting(mut self, actions: &[Action]) -> Self {
+        self.actions = actions.to_vec();
+        self
+    }
+
+    fn challenging(mut self) -> Self {
+        self.challenge = true;
+        self
+    }
+
+    fn blocking(mut self, card: Card) -> Self {
+        self.block = Some (card);
+        self
+    }
+
+    fn losing(mut self, card: Card) -> Self {
+        self.lose = Some (card);
+        self
+    }
+
+    fn swapping(mut self) -> Self {
+        self.swap = true;
+        self
+    }
+}
+
+impl Player for Scripted {
+    fn get_metadata(&self) -> PlayerMetadata {
+        PlayerMetadata::Human
+    }
+
+    fn check(&self, card: Card) -> bool {
+        self.hand.contains(&card)
+    }
+
+    fn replace(&mut self, current: Card, new: Card) {
+        if self.hand[0] == current {
+            self.hand[0] = new;
+        } else {
+            self.hand[1] = new;
+        }
+    }
+
+    /// Keeps as many of the drawn cards as possible.
+    fn exchange(&mut self, _: &GameView, cards: &[Card]) -> Vec<Card> {
+        let live = self.hand.iter()
+            .copied()
+            .filter(|c| *c != Card::None)
+            .collect::<Vec<Card>>();
+        let mut kept = cards.to_vec();
+        kept.extend_from_slice(&live);
+        let returned = kept.split_off(live.len());
+
+        self.hand = [Card::None, Card::None];
+        for (i, card) in kept.iter().enumerate() {
+            self.hand[i] = *card;
+        }
+
+        returned
+    }
+
+    fn get_coins(&self) -> u8 {
+        self.coins
+    }
+
+    /// Keeps the first card of the given hand if it is offered.
+    fn choose_influence(&mut self, _: &GameView, cards: &[Card]) -> Card {
+        if cards.contains(&self.hand[0]) {
+            self.hand[0]
+        } else {
+            cards[0]
+        }
+    }
+
+    fn compute_hands(&mut self, _: &GameView) {}
+
+    fn deal(&mut self, hand: [Card; 2]) {
+        self.hand = hand;
+    }
+
+    fn gain_coins(&mut self, coins: u8) {
+        self.coins += coins;
+    }
+
+    fn lose_coins(&mut self, coins: u8) -> u8 {
+        let lost = coins.min(self.coins);
+        self.coins -= lost;
+        lost
+    }
+
+    fn lose_influence(&mut self, _: &GameView) -> Card {
+        let i = match self.lose {
+            Some (card) if self.hand.contains(&card) => self.hand.iter().position(|c| *c == card).unwrap(),
+            _ => self.hand.iter().position(|c| *c != Card::None).unwrap(),
+        };
+
+        let card = self.hand[i];
+        self.hand[i] = Card::None;
+        card
+    }
+
+    fn check_challenge(&mut self, _: &GameView, _: usize, _: Card) -> bool {
+        self.log.lock().unwrap().push((self.id, "challenge"));
+        self.challenge
+    }
+
+    fn check_block(&mut self, _: &GameView, _: Action) -> (bool, Card) {
+        self.log.lock().unwrap().push((self.id, "block"));
+        match self.block {
+            Some (card) => (true, card),
+            None => (false, Card::None),
+        }
+    }
+
+    /// Shows the first card still in hand.
+    fn show_card(&mut self, _: &GameView, _: usize) -> Card {
+        self.log.lock().unwrap().push((self.id, "show"));
+        self.hand.iter().copied().find(|c| *c != Card::None).unwrap_or(Card::None)
+    }
+
+    fn check_examine(&mut self, _: &GameView, _: usize, _: Card) -> bool {
+        self.swap
+    }
+
+    fn is_eliminated(&self) -> bool {
+        self.hand == [Card::None, Card::None]
+    }
+
+    fn select_action(&mut self, _: &GameView) -> Action {
+        if self.actions.is_empty() {
+            Action::Income
+        } else {
+            self.actions.remove(0)
+        }
+    }
+}
+
+/// Builds an engine that deals each scripted player the hand it was given.
+fn engine(players: Vec<Scripted>) -> Engine {
+    engine_with(RuleSet::default(), players)
+}
+
+/// Builds an engine, as in `engine`, that plays by the given rules.
+fn engine_with(rules: RuleSet, players: Vec<Scripted>) -> Engine {
+    let mut deck = players.iter()
+        .flat_map(|p| p.hand)
+        .collect::<Vec<Card>>();
+    deck.extend_from_slice(&[
+        rules.exchange_card(),
+        Card::Assassin,
+        Card::Captain,
+        Card::Duke,
+    ]);
+
+    let players = players.into_iter()
+        .map(|p| Box::new(p) as Box<dyn Player>)
+        .collect();
+
+    Engine::with_deck(players, rules, deck, 0).unwrap()
+}
+
+fn log() -> Log {
+    Arc::new(Mutex::new(Vec::new()))
+}
+
+fn asked(log: &Log, question: &str) -> Vec<usize> {
+    log.lock().unwrap()
+        .iter()
+        .filter(|(_, q)| *q == question)
+        .map(|(i, _)| *i)
+        .collect()
+}
+
+#[test]
+fn contessa_blocks_assassination_but_coins_are_spent() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Assassin, Card::Duke], &log).coins(3).acting(&[Action::Assassinate (1)]),
+        Scripted::new(1, [Card::Contessa, Card::Captain], &log).blocking(Card::Contessa),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    assert_eq!(engine.get_view(0).coins(), 0);
+    assert_eq!(engine.get_view(1).players[1].influence(), 2);
+}
+
+#[test]
+fn assassination_coins_are_refunded_when_successfully_challenged() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Duke, Card::Captain], &log).coins(3).acting(&[Action::Assassinate (1)]),
+        Scripted::new(1, [Card::Contessa, Card::Captain], &log).challenging(),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(0);
+    assert_eq!(view.coins(), 3);
+    assert_eq!(view.players[0].influence(), 1);
+    assert_eq!(view.players[1].influence(), 2);
+}
+
+#[test]
+fn target_who_loses_a_challenge_can_lose_two_influence() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Assassin, Card::Duke], &log).coins(3).acting(&[Action::Assassinate (1)]),
+        Scripted::new(1, [Card::Captain, Card::Captain], &log).challenging(),
+    ]);
+
+    assert_eq!(engine.turn(false), Ok(Some(0)));
+    assert!(engine.is_eliminated(1));
+}
+
+#[test]
+fn target_can_block_after_losing_a_challenge() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Assassin, Card::Duke], &log).coins(3).acting(&[Action::Assassinate (1)]),
+        Scripted::new(1, [Card::Captain, Card::Contessa], &log).challenging().blocking(Card::Contessa).losing(Card::Captain),
+    ]);
+
+    // Player 1 loses the challenge, blocks, and then Player 0 challenges the
+    // block (and loses, since Player 1 has the Contessa)
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(1);
+    assert_eq!(view.players[1].influence(), 1);
+    assert_eq!(view.players[0].coins, 0);
+}
+
+#[test]
+fn only_the_target_may_block_a_steal() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Captain, Card::Duke], &log).acting(&[Action::Steal (1)]),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log),
+        Scripted::new(2, [Card::Captain, Card::Ambassador], &log).blocking(Card::Captain),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    assert_eq!(asked(&log, "block"), vec![1]);
+    let view = engine.get_view(0);
+    assert_eq!(view.players[0].coins, 4);
+    assert_eq!(view.players[1].coins, 0);
+}
+
+#[test]
+fn any_player_may_block_foreign_aid() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Captain, Card::Duke], &log).acting(&[Action::ForeignAid]),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log),
+        Scripted::new(2, [Card::Duke, Card::Ambassador], &log).blocking(Card::Duke),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    assert_eq!(asked(&log, "block"), vec![1, 2]);
+    assert_eq!(engine.get_view(0).coins(), 2);
+}
+
+#[test]
+fn blocks_with_the_wrong_card_are_rejected() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Captain, Card::Duke], &log).acting(&[Action::ForeignAid]),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log).blocking(Card::Contessa),
+    ]);
+
+    assert_eq!(engine.turn(false), Err(EngineError::IllegalBlock {
+        player: 1,
+        action: Action::ForeignAid,
+        card: Card::Contessa,
+    }));
+}
+
+#[test]
+fn failed_challenge_replaces_the_revealed_card() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Duke, Card::Captain], &log).acting(&[Action::Tax]),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log).challenging(),
+    ]);
+    let deck_size = engine.get_view(0).deck_size;
+
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(0);
+    assert_eq!(view.coins(), 5);
+    assert_eq!(view.players[0].influence(), 2);
+    assert_eq!(view.players[1].influence(), 1);
+    assert_eq!(view.deck_size, deck_size);
+    assert!(engine.get_events().iter().any(|e| matches!(e, GameEvent::CardReplaced { player: 0, returned: Card::Duke, .. })));
+}
+
+#[test]
+fn successful_challenge_prevents_the_action() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Captain, Card::Contessa], &log).acting(&[Action::Tax]),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log).challenging(),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(0);
+    assert_eq!(view.coins(), 2);
+    assert_eq!(view.players[0].influence(), 1);
+    assert_eq!(view.players[1].influence(), 2);
+}
+
+#[test]
+fn successful_block_challenge_lets_the_action_proceed() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Captain, Card::Contessa], &log).acting(&[Action::ForeignAid]).challenging(),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log).blocking(Card::Duke),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(0);
+    assert_eq!(view.coins(), 4);
+    assert_eq!(view.players[1].influence(), 1);
+}
+
+#[test]
+fn failed_block_challenge_stops_the_action() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Captain, Card::Contessa], &log).acting(&[Action::ForeignAid]).challenging(),
+        Scripted::new(1, [Card::Duke, Card::Contessa], &log).blocking(Card::Duke),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(0);
+    assert_eq!(view.coins(), 2);
+    assert_eq!(view.players[0].influence(), 1);
+    assert_eq!(view.players[1].influence(), 2);
+}
+
+#[test]
+fn coup_cannot_be_challenged_or_blocked() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Captain, Card::Contessa], &log).coins(7).acting(&[Action::Coup (1)]),
+        Scripted::new(1, [Card::Duke, Card::Contessa], &log).challenging().blocking(Card::Contessa),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    assert!(log.lock().unwrap().is_empty());
+    let view = engine.get_view(0);
+    assert_eq!(view.coins(), 0);
+    assert_eq!(view.players[1].influence(), 1);
+}
+
+#[test]
+fn ten_coins_forces_a_coup() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Captain, Card::Contessa], &log).coins(10).acting(&[Action::Income]),
+        Scripted::new(1, [Card::Duke, Card::Contessa], &log),
+    ]);
+
+    assert_eq!(engine.legal_actions(0), vec![Action::Coup (1)]);
+    assert_eq!(engine.turn(false), Err(EngineError::IllegalAction {
+        player: 0,
+        action: Action::Income,
+    }));
+    assert_eq!(engine.get_view(0).coins(), 10);
+}
+
+#[test]
+fn a_win_on_the_last_turn_counts() {
+    let log = log();
+    let agent = Genome {
+        utilities: ActionUtilities {
+            coup: 5.0,
+            ..Genome::default().utilities
+        },
+        ..Genome::default()
+    }.agent(1, 1);
+    let players: Vec<Box<dyn Player>> = vec![
+        Box::new(Scripted::new(0, [Card::Captain, Card::Contessa], &log).acting(&[Action::Income, Action::Income])),
+        Box::new(agent),
+    ];
+    let deck = vec![
+        Card::Captain,
+        Card::Contessa,
+        Card::Duke,
+        Card::Assassin,
+        Card::Ambassador,
+        Card::Duke,
+    ];
+    let rules = RuleSet {
+        coup_cost: 1,
+        max_turns: 4,
+        ..RuleSet::default()
+    };
+    let mut engine = Engine::with_deck(players, rules, deck, 0).unwrap();
+
+    // The agent coups Player 0 on the second and fourth turns
+    let winner = engine.play(false).unwrap();
+
+    assert!(matches!(winner, PlayerMetadata::Computer { .. }));
+}
+
+#[test]
+fn actions_cannot_target_yourself_or_eliminated_players() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Assassin, Card::Duke], &log).coins(3).acting(&[Action::Assassinate (1)]),
+        Scripted::new(1, [Card::Captain, Card::Captain], &log).challenging(),
+        Scripted::new(2, [Card::Duke, Card::Contessa], &log),
+    ]);
+
+    assert!(engine.validate_action(0, Action::Steal (0)).is_err());
+
+    engine.turn(false).unwrap();
+
+    assert!(engine.is_eliminated(1));
+    assert!(engine.validate_action(2, Action::Steal (1)).is_err());
+    assert!(engine.validate_action(2, Action::Steal (0)).is_ok());
+}
+
+#[test]
+fn eliminated_players_are_skipped() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Assassin, Card::Duke], &log).coins(3).acting(&[Action::Assassinate (1)]),
+        Scripted::new(1, [Card::Captain, Card::Captain], &log).challenging(),
+        Scripted::new(2, [Card::Duke, Card::Contessa], &log),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    assert_eq!(engine.get_view(0).active_player, 2);
+}
+
+#[test]
+fn challenges_are_simultaneous_and_resolved_in_turn_order() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Duke, Card::Captain], &log).challenging(),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log).acting(&[Action::Tax]),
+        Scripted::new(2, [Card::Captain, Card::Contessa], &log),
+        Scripted::new(3, [Card::Assassin, Card::Assassin], &log).challenging(),
+    ]);
+
+    // Player 0 takes Income, then Player 1 claims Duke
+    engine.turn(false).unwrap();
+    log.lock().unwrap().clear();
+    engine.turn(false).unwrap();
+
+    // Everybody is asked, but Player 3 is the first challenger after Player 1
+    assert_eq!(asked(&log, "challenge"), vec![2, 3, 0]);
+    let view = engine.get_view(1);
+    assert_eq!(view.players[0].influence(), 2);
+    assert_eq!(view.players[1].influence(), 1);
+    assert_eq!(view.players[3].influence(), 2);
+}
+
+#[test]
+fn exchange_with_one_influence_keeps_one_card() {
+    let log = log();
+    let mut engine = engine(vec![
+        Scripted::new(0, [Card::Ambassador, Card::Contessa], &log).acting(&[Action::Tax, Action::Exchange]).losing(Card::Contessa),
+        Scripted::new(1, [Card::Duke, Card::Duke], &log).challenging(),
+    ]);
+
+    // Player 0 is caught bluffing and loses the Contessa
+    engine.turn(false).unwrap();
+    engine.turn(false).unwrap();
+
+    // Player 0 exchanges with one influence, and Player 1 loses the challenge
+    let before = engine.get_view(0).deck_size;
+    assert_eq!(engine.turn(false), Ok(None));
+
+    let view = engine.get_view(0);
+    assert_eq!(view.deck_size, before);
+    assert_eq!(view.hand.iter().filter(|c| **c == Card::None).count(), 1);
+}
+
+#[test]
+fn steal_takes_at_most_the_targets_coins() {
+    let log = log();
+    let rules = RuleSet {
+        starting_coins: 1,
+        ..RuleSet::default()
+    };
+    let mut engine = engine_with(rules, vec![
+        Scripted::new(0, [Card::Captain, Card::Duke], &log).acting(&[Action::Steal (1)]),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(0);
+    assert_eq!(view.players[0].coins, 2);
+    assert_eq!(view.players[1].coins, 0);
+}
+
+#[test]
+fn house_rules_change_costs_and_payouts() {
+    let log = log();
+    let rules = RuleSet {
+        starting_coins: 5,
+        tax: 4,
+        coup_cost: 5,
+        ..RuleSet::default()
+    };
+    let mut engine = engine_with(rules, vec![
+        Scripted::new(0, [Card::Duke, Card::Captain], &log).acting(&[Action::Tax]),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log),
+    ]);
+
+    assert!(engine.legal_actions(0).contains(&Action::Coup (1)));
+    engine.turn(false).unwrap();
+    assert_eq!(engine.get_view(0).coins(), 9);
+
+    // The rules are saved with the record, and the replay follows them
+    let record = engine.get_record().to_string().parse::<GameRecord>().unwrap();
+    assert_eq!(record.rules, rules);
+    assert_eq!(record.engine_at(1).unwrap().get_events(), engine.get_events());
+}
+
+#[test]
+fn agent_blocks_assassination_with_contessa() {
+    let log = log();
+    let agent = Genome::default().agent(1, 1);
+
+    let deck = vec![
+        Card::Assassin,
+        Card::Duke,
+        Card::Contessa,
+        Card::Captain,
+        Card::Ambassador,
+        Card::Duke,
+    ];
+    let players: Vec<Box<dyn Player>> = vec![
+        Box::new(Scripted::new(0, [Card::Assassin, Card::Duke], &log).coins(3).acting(&[Action::Assassinate (1)])),
+        Box::new(agent),
+    ];
+    let mut engine = Engine::with_deck(players, RuleSet::default(), deck, 0).unwrap();
+
+    engine.turn(false).unwrap();
+
+    assert!(engine.get_events().iter().any(|e| matches!(e, GameEvent::Block { blocker: 1, card: Card::Contessa, .. })));
+    assert_eq!(engine.get_view(1).players[1].influence(), 2);
+}
+
+/// Builds an agent that values Exchange most, then Tax, and little else.
+fn exchanger(id: usize) -> Agent {
+    Genome {
+        utilities: ActionUtilities {
+            tax: 9.0,
+            exchange: 10.0,
+            ..Genome::default().utilities
+        },
+        ..Genome::default()
+    }.agent(id, 1)
+}
+
+#[test]
+fn agent_exchange_keeps_the_best_hand() {
+    let log = log();
+    let deck = vec![
+        Card::Ambassador,
+        Card::Contessa,
+        Card::Captain,
+        Card::Captain,
+        Card::Duke,
+        Card::Assassin,
+    ];
+    let players: Vec<Box<dyn Player>> = vec![
+        Box::new(exchanger(0)),
+        Box::new(Scripted::new(1, [Card::Captain, Card::Captain], &log)),
+    ];
+    let mut engine = Engine::with_deck(players, RuleSet::default(), deck, 0).unwrap();
+
+    engine.turn(false).unwrap();
+
+    assert!(engine.get_events().iter().any(|e| matches!(e, GameEvent::Exchange { player: 0, .. })));
+    let mut hand = engine.get_view(0).hand;
+    hand.sort_by_key(|card| *card as u8);
+    assert_eq!(hand, [Card::Duke, Card::Ambassador]);
+}
+
+#[test]
+fn agent_exchange_with_one_influence_keeps_one_card() {
+    let mut agent = exchanger(0);
+    agent.deal([Card::None, Card::Contessa]);
+
+    // Player 0 has already lost an Assassin
+    let engine = engine(vec![
+        Scripted::new(0, [Card::Duke, Card::Contessa], &log()),
+        Scripted::new(1, [Card::Captain, Card::Captain], &log()),
+    ]);
+    let mut view = engine.get_view(0);
+    view.hand = [Card::None, Card::Contessa];
+    view.players[0].revealed = vec![Card::Assassin];
+
+    let mut returned = agent.exchange(&view, &[Card::Duke, Card::Assassin]);
+    returned.sort_by_key(|card| *card as u8);
+
+    assert_eq!(returned, [Card::Assassin, Card::Contessa]);
+    assert!(agent.check(Card::Duke));
+    assert!(!agent.check(Card::Contessa));
+}
+
+#[test]
+fn agent_loses_the_card_it_values_least() {
+    let log = log();
+    let deck = vec![
+        Card::Assassin,
+        Card::Duke,
+        Card::Captain,
+        Card::Duke,
+        Card::Ambassador,
+        Card::Contessa,
+    ];
+    let players: Vec<Box<dyn Player>> = vec![
+        Box::new(Scripted::new(0, [Card::Assassin, Card::Duke], &log).coins(7).acting(&[Action::Coup (1)])),
+        Box::new(exchanger(1)),
+    ];
+    let mut engine = Engine::with_deck(players, RuleSet::default(), deck, 0).unwrap();
+
+    engine.turn(false).unwrap();
+
+    // Tax is worth more to the agent than Steal
+    assert!(engine.get_events().iter().any(|e| matches!(e, GameEvent::InfluenceLost { player: 1, card: Card::Captain })));
+}
+
+/// Builds an agent that only wants to Coup, and picks its target by coins
+/// alone, with the given weight and temperature.
+fn targeter(coins: f64, temperature: f64) -> Agent {
+    Genome {
+        utilities: ActionUtilities {
+            coup: 5.0,
+            ..Genome::default().utilities
+        },
+        targeting: TargetWeights {
+            coins,
+            ..TargetWeights::default()
+        },
+        temperature,
+        ..Genome::default()
+    }.agent(0, 2)
+}
+
+#[test]
+fn agent_targets_opponents_by_its_weights() {
+    let engine = engine(vec![
+        Scripted::new(0, [Card::Duke, Card::Contessa], &log()),
+        Scripted::new(1, [Card::Captain, Card::Captain], &log()),
+        Scripted::new(2, [Card::Assassin, Card::Ambassador], &log()),
+    ]);
+    let mut view = engine.get_view(0);
+    view.players[0].coins = 7;
+    view.players[2].coins = 6;
+    view.legal_actions = vec![Action::Coup (1), Action::Coup (2)];
+
+    // The richer opponent is the bigger target, unless coins count against it
+    for (coins, target) in [(1.0, 2), (-1.0, 1)] {
+        let mut agent = targeter(coins, 0.0);
+        agent.deal([Card::Duke, Card::Contessa]);
+        agent.compute_hands(&view);
+
+        assert_eq!(agent.select_action(&view), Action::Coup (target));
+    }
+}
+
+#[test]
+fn agent_temperature_makes_its_choices_unpredictable() {
+    let engine = engine(vec![
+        Scripted::new(0, [Card::Duke, Card::Contessa], &log()),
+        Scripted::new(1, [Card::Captain, Card::Captain], &log()),
+        Scripted::new(2, [Card::Assassin, Card::Ambassador], &log()),
+    ]);
+    let mut view = engine.get_view(0);
+    view.players[0].coins = 7;
+    view.legal_actions = vec![Action::Coup (1), Action::Coup (2)];
+
+    let choices = |temperature: f64| (0..20)
+        .map(|seed| {
+            let mut agent = targeter(0.0, temperature);
+            agent.reseed(seed);
+            agent.deal([Card::Duke, Card::Contessa]);
+            agent.compute_hands(&view);
+            agent.select_action(&view)
+        })
+        .collect::<Vec<Action>>();
+
+    // Both targets are equally good, so a cold agent always takes the first
+    assert!(choices(0.0).iter().all(|action| *action == Action::Coup (1)));
+    let warm = choices(1.0);
+    assert!(warm.contains(&Action::Coup (1)) && warm.contains(&Action::Coup (2)));
+}
+
+#[test]
+fn agent_utility_model_adapts_to_the_game() {
+    let mut agent = Genome {
+        utilities: ActionUtilities {
+            income: 4.0,
+            coup: 5.0,
+            ..Genome::default().utilities
+        },
+        model: UtilityModel {
+            coup: FeatureWeights {
+                opponent_influence: -1.5,
+                ..FeatureWeights::default()
+            },
+            ..UtilityModel::default()
+        },
+        ..Genome::default()
+    }.agent(0, 1);
+    agent.deal([Card::Duke, Card::Contessa]);
+
+    let engine = engine(vec![
+        Scripted::new(0, [Card::Duke, Card::Contessa], &log()),
+        Scripted::new(1, [Card::Captain, Card::Captain], &log()),
+    ]);
+    let mut view = engine.get_view(0);
+    view.players[0].coins = 7;
+    view.legal_actions = vec![Action::Income, Action::Coup (1)];
+
+    // A Coup is only worth its cost against an opponent with one card left
+    agent.compute_hands(&view);
+    assert_eq!(agent.select_action(&view), Action::Income);
+
+    view.players[1].revealed = vec![Card::Captain];
+    agent.compute_hands(&view);
+    assert_eq!(agent.select_action(&view), Action::Coup (1));
+}
+
+#[test]
+fn agent_with_one_card_left_challenges_less() {
+    let mut agent = Genome {
+        liar_cutoff: 0.3,
+        ..Genome::default()
+    }.agent(1, 1);
+    agent.deal([Card::Duke, Card::Contessa]);
+
+    let engine = engine(vec![
+        Scripted::new(0, [Card::Captain, Card::Contessa], &log()),
+        Scripted::new(1, [Card::Duke, Card::Contessa], &log()),
+    ]);
+    let mut view = engine.get_view(1);
+    view.current = Some (Action::Tax);
+
+    // Player 0 probably has no Duke, which is worth a card to find out...
+    assert!(agent.check_challenge(&view, 0, Card::Duke));
+
+    // ...but not the last card
+    view.hand = [Card::Duke, Card::None];
+    view.players[1].revealed = vec![Card::Contessa];
+    assert!(!agent.check_challenge(&view, 0, Card::Duke));
+}
+
+/// Builds an agent that blocks Foreign Aid for the table with the given
+/// weight, and stands by the cards it has claimed.
+fn blocker(id: usize, aid: f64) -> Agent {
+    Genome {
+        blocking: BlockWeights {
+            aid,
+            consistency: 1.0,
+            ..BlockWeights::default()
+        },
+        ..Genome::default()
+    }.agent(id, 2)
+}
+
+#[test]
+fn agent_blocks_foreign_aid_for_the_table() {
+    for (aid, blocked) in [(1.0, true), (0.0, true)] {
+        let log = log();
+        let players: Vec<Box<dyn Player>> = vec![
+            Box::new(Scripted::new(0, [Card::Captain, Card::Contessa], &log).coins(5).acting(&[Action::ForeignAid])),
+            Box::new(Scripted::new(1, [Card::Assassin, Card::Ambassador], &log)),
+            Box::new(blocker(2, aid)),
+        ];
+        let deck = vec![
+            Card::Captain,
+            Card::Contessa,
+            Card::Assassin,
+            Card::Ambassador,
+            Card::Duke,
+            Card::Contessa,
+            Card::Duke,
+        ];
+        let mut engine = Engine::with_deck(players, RuleSet::default(), deck, 0).unwrap();
+
+        engine.turn(false).unwrap();
+
+        // Player 2 is not the one getting the aid, but it holds a Duke, so
+        // it blocks honestly whatever its aid weight
+        let block = engine.get_events().iter().any(|e| matches!(e, GameEvent::Block { blocker: 2, card: Card::Duke, .. }));
+        assert_eq!(block, blocked);
+    }
+}
+
+#[test]
+fn agent_bluffs_blocks_with_cards_it_has_claimed() {
+    let mut agent = blocker(1, 0.0);
+    agent.deal([Card::Duke, Card::Contessa]);
+
+    let engine = engine(vec![
+        Scripted::new(0, [Card::Captain, Card::Assassin], &log()),
+        Scripted::new(1, [Card::Duke, Card::Contessa], &log()),
+        Scripted::new(2, [Card::Assassin, Card::Duke], &log()),
+    ]);
+    let mut view = engine.get_view(1);
+    view.current = Some (Action::Steal (1));
+    agent.compute_hands(&view);
+
+    // A Captain or an Ambassador out of nowhere is too likely to be called
+    assert!(!agent.check_block(&view, Action::Steal (1)).0);
+
+    // But this player already blocked with an Ambassador, so it does again
+    view.history.push(GameEvent::Block {
+        blocker: 1,
+        actor: 2,
+        action: Action::Steal (1),
+        card: Card::Ambassador,
+    });
+    assert_eq!(agent.check_block(&view, Action::Steal (1)), (true, Card::Ambassador));
+}
+
+fn reformation() -> RuleSet {
+    RuleSet {
+        reformation: true,
+        ..RuleSet::default()
+    }
+}
+
+#[test]
+fn reformation_protects_members_of_the_same_faction() {
+    let log = log();
+    let mut engine = engine_with(reformation(), vec![
+        Scripted::new(0, [Card::Captain, Card::Duke], &log).acting(&[Action::ForeignAid]),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log),
+        Scripted::new(2, [Card::Duke, Card::Ambassador], &log).blocking(Card::Duke),
+    ]);
+
+    let view = engine.get_view(0);
+    assert_eq!(view.players[0].faction, Some (Faction::Loyalist));
+    assert_eq!(view.players[1].faction, Some (Faction::Reformist));
+    assert_eq!(view.players[2].faction, Some (Faction::Loyalist));
+    assert!(engine.legal_actions(0).contains(&Action::Steal (1)));
+    assert!(!engine.legal_actions(0).contains(&Action::Steal (2)));
+
+    // Player 2 would block, but may not block a fellow Loyalist
+    engine.turn(false).unwrap();
+
+    assert_eq!(asked(&log, "block"), vec![1]);
+    assert_eq!(engine.get_view(0).coins(), 4);
+}
+
+#[test]
+fn reformation_protection_ends_when_one_faction_is_left() {
+    let log = log();
+    let mut engine = engine_with(reformation(), vec![
+        Scripted::new(0, [Card::Assassin, Card::Duke], &log).coins(3).acting(&[Action::Assassinate (1)]),
+        Scripted::new(1, [Card::Captain, Card::Captain], &log).challenging(),
+        Scripted::new(2, [Card::Duke, Card::Contessa], &log),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    assert!(engine.is_eliminated(1));
+    assert!(!engine.allied(0, 2));
+    assert!(engine.legal_actions(2).contains(&Action::Steal (0)));
+}
+
+#[test]
+fn convert_pays_into_the_reserve_and_embezzle_takes_it() {
+    let log = log();
+    let mut engine = engine_with(reformation(), vec![
+        Scripted::new(0, [Card::Captain, Card::Duke], &log).acting(&[Action::Convert (1)]),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log).acting(&[Action::Embezzle]),
+    ]);
+
+    assert!(!engine.legal_actions(1).contains(&Action::Embezzle));
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(1);
+    assert_eq!(view.players[0].coins, 0);
+    assert_eq!(view.players[1].faction, Some (Faction::Loyalist));
+    assert_eq!(view.reserve, 2);
+
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(1);
+    assert_eq!(view.coins(), 4);
+    assert_eq!(view.reserve, 0);
+}
+
+#[test]
+fn embezzle_challenge_succeeds_against_a_duke() {
+    let log = log();
+    let mut engine = engine_with(reformation(), vec![
+        Scripted::new(0, [Card::Captain, Card::Contessa], &log).acting(&[Action::Convert (0)]).challenging(),
+        Scripted::new(1, [Card::Duke, Card::Contessa], &log).acting(&[Action::Embezzle]),
+    ]);
+
+    engine.turn(false).unwrap();
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(1);
+    assert_eq!(view.players[1].influence(), 1);
+    assert_eq!(view.coins(), 2);
+    assert_eq!(view.reserve, 1);
+}
+
+#[test]
+fn failed_embezzle_challenge_replaces_the_hand() {
+    let log = log();
+    let mut engine = engine_with(reformation(), vec![
+        Scripted::new(0, [Card::Captain, Card::Contessa], &log).acting(&[Action::Convert (0)]).challenging(),
+        Scripted::new(1, [Card::Assassin, Card::Contessa], &log).acting(&[Action::Embezzle]),
+    ]);
+    let deck_size = engine.get_view(1).deck_size;
+
+    engine.turn(false).unwrap();
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(1);
+    assert_eq!(view.players[0].influence(), 1);
+    assert_eq!(view.players[1].influence(), 2);
+    assert_eq!(view.coins(), 3);
+    assert_eq!(view.deck_size, deck_size);
+    let replaced = engine.get_events().iter()
+        .filter(|e| matches!(e, GameEvent::CardReplaced { player: 1, .. }))
+        .count();
+    assert_eq!(replaced, 2);
+}
+
+fn inquisitor() -> RuleSet {
+    RuleSet {
+        inquisitor: true,
+        ..RuleSet::default()
+    }
+}
+
+#[test]
+fn inquisitor_replaces_the_ambassador() {
+    let log = log();
+    let rules = inquisitor();
+    assert!(rules.deck().contains(&Card::Inquisitor));
+    assert!(!rules.deck().contains(&Card::Ambassador));
+
+    let mut engine = engine_with(rules, vec![
+        Scripted::new(0, [Card::Captain, Card::Duke], &log).acting(&[Action::Steal (1)]).challenging(),
+        Scripted::new(1, [Card::Inquisitor, Card::Contessa], &log).blocking(Card::Inquisitor),
+    ]);
+
+    // Player 0 challenges the block and loses
+    engine.turn(false).unwrap();
+
+    let view = engine.get_view(0);
+    assert_eq!(view.coins(), 2);
+    assert_eq!(view.players[0].influence(), 1);
+    assert!(engine.legal_actions(1).contains(&Action::Examine (0)));
+}
+
+#[test]
+fn inquisitor_exchange_draws_one_card() {
+    let log = log();
+    let mut engine = engine_with(inquisitor(), vec![
+        Scripted::new(0, [Card::Inquisitor, Card::Duke], &log).acting(&[Action::Exchange]),
+        Scripted::new(1, [Card::Contessa, Card::Contessa], &log),
+    ]);
+    let deck_size = engine.get_view(0).deck_size;
+
+    engine.turn(false).unwrap();
+
+    assert_eq!(engine.get_view(0).deck_size, deck_size);
+    assert!(engine.get_events().iter().any(|e| matches!(e, GameEvent::Exchange { player: 0, drawn, .. } if drawn.len() == 1)));
+}
+
+#[test]
+fn examine_can_force_a_swap() {
+    let log = log();
+    let mut engine = engine_with(inquisitor(), vec![
+        Scripted::new(0, [Card::Inquisitor, Card::Duke], &log).acting(&[Action::Examine (1)]).swapping(),
+        Scripted::new(1, [Card::Assassin, Card::Contessa], &log),
+    ]);
+    let deck_size = engine.get_view(0).deck_size;
+
+    engine.turn(false).unwrap();
+
+    assert_eq!(asked(&log, "show"), vec![1]);
+    assert_eq!(engine.get_view(1).deck_size, deck_size);
+    let swap = engine.get_events().iter()
+        .find(|e| matches!(e, GameEvent::CardSwapped { .. }))
+        .unwrap();
+    assert!(matches!(swap, GameEvent::CardSwapped { player: 1, examiner: 0, returned: Card::Assassin, .. }));
+    assert!(matches!(swap.public(), GameEvent::CardSwapped { returned: Card::None, drawn: Card::None, .. }));
+}
+
+#[test]
+fn examine_without_a_swap_leaves_the_hand_alone() {
+    let log = log();
+    let mut engine = engine_with(inquisitor(), vec![
+        Scripted::new(0, [Card::Inquisitor, Card::Duke], &log).acting(&[Action::Examine (1)]),
+        Scripted::new(1, [Card::Assassin, Card::Contessa], &log),
+    ]);
+
+    engine.turn(false).unwrap();
+
+    assert_eq!(engine.get_view(1).hand, [Card::Assassin, Card::Contessa]);
+    assert!(engine.get_events().iter().any(|e| matches!(e, GameEvent::CardShown { player: 1, examiner: 0, card: Card::Assassin })));
+    assert!(!engine.get_events().iter().any(|e| matches!(e, GameEvent::CardSwapped { .. })));
+}
+
+fn agents(count: usize) -> Vec<Box<dyn Player>> {
+    (0..count)
+        .map(|i| Box::new(Agent::with_seed(i, count - 1, i as u64)) as Box<dyn Player>)
+        .collect()
+}
+
+#[test]
+fn invalid_player_counts_are_rejected() {
+    assert!(matches!(Engine::with_seed(agents(1), RuleSet::default(), 0), Err(EngineError::InvalidPlayerCount { players: 1 })));
+    assert!(matches!(Engine::with_seed(agents(3), RuleSet::for_players(2), 0), Err(EngineError::InvalidPlayerCount { players: 3 })));
+}
+
+#[test]
+fn small_decks_are_rejected() {
+    assert!(matches!(Engine::with_seed(agents(8), RuleSet::default(), 0), Err(EngineError::InvalidDeck { players: 8, cards: 15 })));
+    assert!(Engine::with_seed(agents(8), RuleSet::for_players(8), 0).is_ok());
+}
+
+#[test]
+fn the_deck_scales_with_the_number_of_players() {
+    assert_eq!(RuleSet::for_players(6).deck_size(), 15);
+    assert_eq!(RuleSet::for_players(7).deck_size(), 20);
+    assert_eq!(RuleSet::for_players(10).deck_size(), 25);
+
+    let mut engine = Engine::with_seed(agents(10), RuleSet::for_players(10), 0).unwrap();
+    assert_eq!(engine.get_view(0).deck_size, 5);
+    assert!(engine.play(false).is_ok());
+}
+
+#[test]
+fn head_to_head_players_choose_their_hand() {
+    let log = log();
+    let players: Vec<Box<dyn Player>> = vec![
+        Box::new(Scripted::new(0, [Card::Duke, Card::None], &log)),
+        Box::new(Scripted::new(1, [Card::Assassin, Card::None], &log)),
+    ];
+    let mut engine = Engine::with_seed(players, RuleSet::for_players(2), 0).unwrap();
+
+    assert_eq!(engine.get_view(0).hand[0], Card::Duke);
+    assert_eq!(engine.get_view(1).hand[0], Card::Assassin);
+    assert_eq!(engine.get_view(0).deck_size, 11);
+
+    // The first player starts with one coin fewer
+    assert_eq!(engine.get_view(0).coins(), 1);
+    assert_eq!(engine.get_view(1).coins(), 2);
+
+    engine.turn(false).unwrap();
+
+    let record = engine.get_record().to_string().parse::<GameRecord>().unwrap();
+    assert_eq!(record.setup.len(), 2);
+    assert_eq!(record.engine_at(1).unwrap().get_events(), engine.get_events());
+}
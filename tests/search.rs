@@ -0,0 +1,90 @@
+//! Checks that the searching player plays legal, reproducible games.
+
+use contessa::{
+    Card,
+    Agent,
+    ActionUtilities,
+    Budget,
+    Engine,
+    GameEvent,
+    Player,
+    RuleSet,
+    Searcher,
+};
+
+mod common;
+
+use common::Genome;
+
+/// Builds a game of one searcher (Player 0) against agents.
+fn game(rules: RuleSet, players: usize, seed: u64) -> Engine {
+    let mut list: Vec<Box<dyn Player>> = vec![Box::new(Searcher::with_seed(0, Budget::Iterations (10), seed))];
+    for i in 1..players {
+        list.push(Box::new(Agent::with_seed(i, players - 1, seed + i as u64)));
+    }
+
+    Engine::with_seed(list, rules, seed).unwrap()
+}
+
+#[test]
+fn searcher_plays_legal_games_under_every_rule_set() {
+    let variants = [
+        (RuleSet::default(), 4),
+        (RuleSet { reformation: true, ..RuleSet::default() }, 4),
+        (RuleSet { inquisitor: true, ..RuleSet::default() }, 3),
+        (RuleSet::for_players(2), 2),
+    ];
+
+    for (rules, players) in variants {
+        let mut engine = game(rules, players, 7);
+        assert!(engine.play(false).is_ok());
+    }
+}
+
+#[test]
+fn searcher_games_are_reproducible() {
+    let mut first = game(RuleSet::default(), 3, 3);
+    let mut second = game(RuleSet::default(), 3, 3);
+
+    for _ in 0..20 {
+        first.turn(false).unwrap();
+        second.turn(false).unwrap();
+    }
+
+    assert_eq!(first.get_events(), second.get_events());
+}
+
+#[test]
+fn searcher_challenges_a_claim_it_knows_is_false() {
+    // An agent that prefers Tax and is willing to lie about anything
+    let liar = Genome {
+        utilities: ActionUtilities {
+            tax: 10.0,
+            ..Genome::default().utilities
+        },
+        ..Genome::default()
+    }.agent(0, 1);
+
+    // The searcher holds both Dukes in the game
+    let rules = RuleSet {
+        copies: 2,
+        ..RuleSet::default()
+    };
+    let deck = vec![
+        Card::Captain,
+        Card::Contessa,
+        Card::Duke,
+        Card::Duke,
+        Card::Assassin,
+        Card::Ambassador,
+    ];
+    let players: Vec<Box<dyn Player>> = vec![
+        Box::new(liar),
+        Box::new(Searcher::with_seed(1, Budget::Iterations (200), 0)),
+    ];
+    let mut engine = Engine::with_deck(players, rules, deck, 0).unwrap();
+
+    engine.turn(false).unwrap();
+
+    assert!(engine.get_events().iter().any(|e| matches!(e, GameEvent::ChallengeResolved { challenger: 1, claimant: 0, success: true, .. })));
+}
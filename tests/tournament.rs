@@ -0,0 +1,76 @@
+//! Checks that tournaments schedule fair tables and rate every entrant.
+
+use contessa::{
+    Agent,
+    Entrant,
+    Player,
+    RuleSet,
+    Standing,
+    Tournament,
+    TournamentConfig,
+};
+
+/// A small, quick tournament.
+fn config(threads: usize) -> TournamentConfig {
+    TournamentConfig {
+        rounds: 2,
+        rules: RuleSet {
+            max_turns: 100,
+            ..RuleSet::default()
+        },
+        resamples: 20,
+        threads,
+        ..TournamentConfig::default()
+    }
+}
+
+/// A tournament between the given number of random agents.
+fn tournament(entrants: usize, threads: usize) -> Tournament {
+    let mut tournament = Tournament::with_seed(config(threads), 12);
+    for i in 0..entrants {
+        let genome = Agent::with_seed(0, 3, i as u64).get_metadata();
+        tournament.enter(&format!("agent{}", i), Entrant::Genome (genome));
+    }
+
+    tournament
+}
+
+#[test]
+fn every_entrant_plays_every_seat_equally() {
+    let tables = tournament(5, 1).schedule();
+
+    // Each of the five groups of four plays four rotations, twice
+    assert_eq!(tables.len(), 5*4*2);
+    for entrant in 0..5 {
+        for seat in 0..4 {
+            let count = tables.iter().filter(|table| table[seat] == entrant).count();
+            assert_eq!(count, 8);
+        }
+    }
+}
+
+#[test]
+fn tournaments_do_not_depend_on_the_number_of_threads() {
+    let serial = tournament(4, 1).play().unwrap();
+    let parallel = tournament(4, 4).play().unwrap();
+
+    let ratings = |standings: &[Standing]| standings.iter()
+        .map(|standing| (standing.name.clone(), standing.rating, standing.low, standing.high))
+        .collect::<Vec<(String, f64, f64, f64)>>();
+    assert_eq!(ratings(&serial.standings), ratings(&parallel.standings));
+}
+
+#[test]
+fn leaderboard_ranks_every_entrant() {
+    let leaderboard = tournament(3, 2).play().unwrap();
+
+    assert_eq!(leaderboard.standings.len(), 3);
+    for pair in leaderboard.standings.windows(2) {
+        assert!(pair[0].rating >= pair[1].rating);
+    }
+    for standing in &leaderboard.standings {
+        assert_eq!(standing.games, leaderboard.games);
+        assert!(standing.low <= standing.high);
+        assert!((0.0..=1.0).contains(&standing.wins));
+    }
+}
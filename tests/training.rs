@@ -0,0 +1,94 @@
+//! Checks that the genetic algorithm selects, breeds, and reports as
+//! configured.
+
+use rand::{
+    SeedableRng,
+    rngs::StdRng,
+};
+
+use contessa::{
+    ActionUtilities,
+    PlayerMetadata,
+    Population,
+    TrainingConfig,
+};
+
+/// A small, quick training run.
+fn config() -> TrainingConfig {
+    TrainingConfig {
+        population: 8,
+        games: 2,
+        elites: 2,
+        ..TrainingConfig::default()
+    }
+}
+
+#[test]
+fn training_is_reproducible() {
+    let mut first = Population::with_seed(config(), 4);
+    let mut second = Population::with_seed(config(), 4);
+    first.evolve(2).unwrap();
+    second.evolve(2).unwrap();
+
+    assert_eq!(first.get_genomes(), second.get_genomes());
+    assert_eq!(first.get_generation(), 2);
+}
+
+#[test]
+fn training_does_not_depend_on_the_number_of_threads() {
+    let mut serial = Population::with_seed(TrainingConfig { threads: 1, ..config() }, 6);
+    let mut parallel = Population::with_seed(TrainingConfig { threads: 4, ..config() }, 6);
+
+    let serial_reports = serial.evolve(2).unwrap();
+    let parallel_reports = parallel.evolve(2).unwrap();
+
+    assert_eq!(serial.get_genomes(), parallel.get_genomes());
+    assert_eq!(serial_reports[1].best, parallel_reports[1].best);
+}
+
+#[test]
+fn elites_survive_and_reports_are_consistent() {
+    let mut population = Population::with_seed(config(), 1);
+    let report = population.step().unwrap();
+
+    assert_eq!(report.generation, 0);
+    assert!(0.0 <= report.worst && report.worst <= report.mean && report.mean <= report.best && report.best <= 1.0);
+    assert!(report.diversity > 0.0);
+    assert_eq!(population.get_genomes().len(), 8);
+    assert_eq!(population.get_genomes()[0], report.champion);
+}
+
+#[test]
+fn mutation_keeps_cutoffs_in_range() {
+    let mut population = Population::with_seed(TrainingConfig {
+        mutation: 0.5,
+        ..config()
+    }, 2);
+    population.evolve(3).unwrap();
+
+    for genome in population.get_genomes() {
+        match genome {
+            PlayerMetadata::Computer { lying_cutoff, liar_cutoff, .. } => {
+                assert!((0.0..=1.0).contains(lying_cutoff));
+                assert!((0.0..=1.0).contains(liar_cutoff));
+            },
+            _ => panic!("expected a computer genome"),
+        }
+    }
+}
+
+#[test]
+fn utility_mutation_has_zero_mean() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let start = ActionUtilities::random(&mut rng);
+
+    let mut utilities = start;
+    for _ in 0..10000 {
+        utilities = utilities.mutate(&mut rng);
+    }
+
+    // A random walk of 10000 steps of up to 0.1 strays about 6 from where it
+    // started, where a walk that only adds would have gone about 500
+    assert!((utilities.tax - start.tax).abs() < 30.0);
+    assert!((utilities.steal - start.steal).abs() < 30.0);
+}